@@ -0,0 +1,302 @@
+use std::path::Path;
+
+use thiserror::Error;
+
+pub const USAGE: &str = "\
+Usage: rmenu [OPTIONS]
+
+Options:
+  -d, --dmenu            Read the entries from stdin and print the selected one.
+  -m, --multi-select     With --dmenu, mark several entries to print.
+  -0, --print0           With --dmenu, end the printed entries with NUL.
+  -P, --password         Hide the input and print it instead of matching it.
+      --pinentry         Talk the pinentry Assuan protocol through stdin.
+  -p, --prompt <PROMPT>  Text shown before the input.
+  -s, --script <NAME>    Show the entries of a script from the configuration.
+      --modes <LIST>     Modes to switch between, such as run,drun,window,<script>.
+      --close-window     In the window mode, close the chosen window instead.
+  -i, --case-insensitive Match ignoring letter case.
+  -h, --help             Print this help and exit.
+  -v, --version          Print the version and exit.";
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum CliError {
+    #[error("The option '{0}' requires a value")]
+    MissingValue(String),
+
+    #[error("Unknown option '{0}'")]
+    UnknownOption(String),
+}
+
+/// The command line options, these take
+/// precedence over the configuration file.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CliOptions {
+    dmenu: bool,
+    multi_select: bool,
+    print0: bool,
+    password: bool,
+    pinentry: bool,
+    prompt: Option<String>,
+    script: Option<String>,
+    modes: Vec<String>,
+    close_window: bool,
+    case_insensitive: bool,
+
+    help: bool,
+    version: bool,
+}
+
+impl CliOptions {
+    /// Parses the arguments without the program name, options
+    /// take their value as the next argument or after an `=`.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, CliError> {
+        let mut options = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let (option, inline_value) = match arg.split_once('=') {
+                Some((option, value)) if option.starts_with("--") => {
+                    (option.to_string(), Some(value.to_string()))
+                },
+                _ => (arg, None),
+            };
+
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| CliError::MissingValue(option.clone()))
+            };
+
+            match option.as_str() {
+                "-d" | "--dmenu" => options.dmenu = true,
+                "-m" | "--multi-select" => options.multi_select = true,
+                "-0" | "--print0" => options.print0 = true,
+                "-P" | "--password" => options.password = true,
+                "--pinentry" => options.pinentry = true,
+                "-p" | "--prompt" => options.prompt = Some(value()?),
+                "-s" | "--script" => options.script = Some(value()?),
+                "--modes" => {
+                    options.modes = value()?
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|mode| !mode.is_empty())
+                        .map(str::to_string)
+                        .collect();
+                },
+                "--close-window" => options.close_window = true,
+                "-i" | "--case-insensitive" => options.case_insensitive = true,
+
+                "-h" | "--help" => options.help = true,
+                "-v" | "--version" => options.version = true,
+
+                _ => return Err(CliError::UnknownOption(option)),
+            }
+        }
+
+        Ok(options)
+    }
+
+    /// Parses the arguments like `parse`, unless the program was started
+    /// through a link ending in `askpass`, which takes the prompt as its
+    /// arguments like `SUDO_ASKPASS` and `SSH_ASKPASS` programs do, or
+    /// in `pinentry`, which ignores them as gpg-agent passes tty options.
+    pub fn for_program(
+        program: &str,
+        args: impl IntoIterator<Item = String>,
+    ) -> Result<Self, CliError> {
+        let name = Path::new(program)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+
+        if name.ends_with("askpass") {
+            let prompt = args
+                .into_iter()
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            return Ok(Self {
+                password: true,
+                prompt: (!prompt.is_empty()).then_some(prompt),
+                ..Self::default()
+            });
+        }
+
+        if name.ends_with("pinentry") {
+            return Ok(Self { pinentry: true, ..Self::default() });
+        }
+
+        Self::parse(args)
+    }
+
+    /// Whether the entries come from stdin and the
+    /// selection is printed instead of started.
+    #[inline]
+    pub const fn dmenu(&self) -> bool {
+        self.dmenu
+    }
+
+    #[inline]
+    pub const fn multi_select(&self) -> bool {
+        self.multi_select
+    }
+
+    /// Whether printed entries end with NUL instead of a newline.
+    #[inline]
+    pub const fn print0(&self) -> bool {
+        self.print0
+    }
+
+    /// Whether the input is a password, printed once accepted.
+    #[inline]
+    pub const fn password(&self) -> bool {
+        self.password
+    }
+
+    #[inline]
+    pub const fn pinentry(&self) -> bool {
+        self.pinentry
+    }
+
+    #[inline]
+    pub fn prompt(&self) -> Option<&str> {
+        self.prompt
+            .as_deref()
+    }
+
+    /// The name of the script whose entries are shown.
+    #[inline]
+    pub fn script(&self) -> Option<&str> {
+        self.script
+            .as_deref()
+    }
+
+    /// The names of the modes to switch between, in order.
+    #[inline]
+    pub fn modes(&self) -> &[String] {
+        &self.modes
+    }
+
+    /// Whether the window mode closes the chosen
+    /// window instead of activating it.
+    #[inline]
+    pub const fn close_window(&self) -> bool {
+        self.close_window
+    }
+
+    #[inline]
+    pub const fn case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+
+    #[inline]
+    pub const fn help(&self) -> bool {
+        self.help
+    }
+
+    #[inline]
+    pub const fn version(&self) -> bool {
+        self.version
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliOptions, CliError> {
+        CliOptions::parse(
+            args.iter()
+                .map(|arg| arg.to_string()),
+        )
+    }
+
+    #[test]
+    fn prompt_takes_the_next_argument_or_an_inline_value() {
+        assert_eq!(
+            parse(&["-p", "Kill process:"])
+                .unwrap()
+                .prompt(),
+            Some("Kill process:")
+        );
+        assert_eq!(
+            parse(&["--prompt=a=b"])
+                .unwrap()
+                .prompt(),
+            Some("a=b")
+        );
+    }
+
+    #[test]
+    fn script_takes_a_name() {
+        assert_eq!(
+            parse(&["--script", "bookmarks", "-i"])
+                .unwrap()
+                .script(),
+            Some("bookmarks")
+        );
+    }
+
+    #[test]
+    fn modes_are_separated_by_commas() {
+        assert_eq!(
+            parse(&["--modes=run, drun,,window"])
+                .unwrap()
+                .modes(),
+            ["run", "drun", "window"]
+        );
+    }
+
+    #[test]
+    fn options_without_a_value_fail() {
+        assert_eq!(parse(&["-p"]).unwrap_err(), CliError::MissingValue("-p".to_string()));
+    }
+
+    #[test]
+    fn unknown_options_fail() {
+        assert_eq!(parse(&["--nope"]).unwrap_err(), CliError::UnknownOption("--nope".to_string()));
+    }
+
+    #[test]
+    fn flags_are_set() {
+        let options =
+            parse(&["-h", "--version", "-i", "-d", "-m", "-0", "--close-window"]).unwrap();
+
+        assert!(options.dmenu());
+        assert!(options.multi_select());
+        assert!(options.print0());
+        assert!(options.help());
+        assert!(options.case_insensitive());
+        assert!(options.version());
+        assert!(options.close_window());
+        assert_eq!(options.prompt(), None);
+    }
+
+    #[test]
+    fn askpass_links_take_the_prompt_as_arguments() {
+        let options = CliOptions::for_program(
+            "/usr/local/bin/rmenu-askpass",
+            ["[sudo]", "password", "for", "me:"].map(str::to_string),
+        )
+        .unwrap();
+
+        assert!(options.password());
+        assert_eq!(options.prompt(), Some("[sudo] password for me:"));
+    }
+
+    #[test]
+    fn pinentry_links_ignore_the_arguments() {
+        let options =
+            CliOptions::for_program("rmenu-pinentry", ["--display", ":0"].map(str::to_string))
+                .unwrap();
+
+        assert!(options.pinentry());
+        assert!(
+            CliOptions::for_program("rmenu", ["-P".to_string()])
+                .unwrap()
+                .password()
+        );
+    }
+}
@@ -0,0 +1,245 @@
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+use sdl2::surface::Surface;
+use toml_edit::Item as TomlItem;
+
+use crate::config::types::ConfigValueError;
+use crate::menu::state::CandidateDetails;
+use crate::utils::shell_words::split_words;
+
+/// An entry defined in the configuration, listed among the
+/// programs and started with its own command, like an alias.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    name: String,
+    description: Option<String>,
+    command: Vec<String>,
+    icon: Option<PathBuf>,
+}
+
+/// The `[[entries]]` of the configuration.
+#[derive(Clone, Debug, Default)]
+pub struct ConfigEntries(Vec<Entry>);
+
+/// The `[aliases]` of the configuration, entries
+/// with nothing but a name and a command.
+#[derive(Clone, Debug, Default)]
+pub struct ConfigAliases(Vec<Entry>);
+
+impl Entry {
+    /// An entry whose command is read with the same quoting rules as the input.
+    pub fn new(name: &str, command: &str) -> Result<Self, ConfigValueError> {
+        let entry_err =
+            |message: String| ConfigValueError::Entry { entry: name.to_string(), message };
+
+        let command = split_words(command).map_err(|err| entry_err(err.to_string()))?;
+
+        if command.is_empty() {
+            return Err(entry_err("has no command".to_string()));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            description: None,
+            command,
+            icon: None,
+        })
+    }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Drawn after the name, dimmed.
+    #[inline]
+    pub fn description(&self) -> Option<&str> {
+        self.description
+            .as_deref()
+    }
+
+    /// The program and its arguments.
+    #[inline]
+    pub fn command(&self) -> &[String] {
+        &self.command
+    }
+
+    /// A BMP image drawn before the name.
+    #[inline]
+    pub fn icon(&self) -> Option<&Path> {
+        self.icon.as_deref()
+    }
+
+    /// How it's drawn among the programs.
+    pub fn details(&self) -> CandidateDetails {
+        let mut details = CandidateDetails::default();
+        details.set_description(
+            self.description
+                .clone(),
+        );
+        details.set_icon(self.icon.clone());
+        details
+    }
+}
+
+impl TryFrom<TomlItem> for Entry {
+    type Error = ConfigValueError;
+
+    fn try_from(value: TomlItem) -> Result<Self, Self::Error> {
+        let Some(table) = value.as_table_like() else {
+            return Err(ConfigValueError::InvalidType { possible_types: vec!["Table"] });
+        };
+
+        let string = |key: &str| {
+            table
+                .get(key)
+                .map(|value| {
+                    value
+                        .as_str()
+                        .ok_or(ConfigValueError::InvalidType { possible_types: vec!["String"] })
+                })
+                .transpose()
+        };
+
+        let name = string("name")?.ok_or_else(|| ConfigValueError::Entry {
+            entry: value
+                .to_string()
+                .trim()
+                .to_string(),
+            message: "has no name".to_string(),
+        })?;
+
+        let mut entry = Self::new(name, string("command")?.unwrap_or_default())?;
+
+        entry.description = string("description")?
+            .filter(|description| !description.is_empty())
+            .map(str::to_string);
+
+        if let Some(icon) = string("icon")? {
+            // Only BMP is supported without SDL_image.
+            Surface::load_bmp(icon).map_err(|err| ConfigValueError::Entry {
+                entry: icon.to_string(),
+                message: format!("isn't a BMP image, {err}"),
+            })?;
+
+            entry.icon = Some(icon.into());
+        }
+
+        Ok(entry)
+    }
+}
+
+impl TryFrom<TomlItem> for ConfigEntries {
+    type Error = ConfigValueError;
+
+    fn try_from(value: TomlItem) -> Result<Self, Self::Error> {
+        // `[[entries]]` tables or an array of inline ones.
+        if let Some(tables) = value.as_array_of_tables() {
+            return tables
+                .iter()
+                .map(|table| Entry::try_from(TomlItem::Table(table.clone())))
+                .collect::<Result<Vec<_>, _>>()
+                .map(Self);
+        }
+
+        if let Some(array) = value.as_array() {
+            return array
+                .iter()
+                .map(|table| Entry::try_from(TomlItem::Value(table.clone())))
+                .collect::<Result<Vec<_>, _>>()
+                .map(Self);
+        }
+
+        Err(ConfigValueError::InvalidType { possible_types: vec!["Vec<Table>"] })
+    }
+}
+
+impl TryFrom<TomlItem> for ConfigAliases {
+    type Error = ConfigValueError;
+
+    fn try_from(value: TomlItem) -> Result<Self, Self::Error> {
+        let Some(table) = value.as_table_like() else {
+            return Err(ConfigValueError::InvalidType { possible_types: vec!["Table"] });
+        };
+
+        table
+            .iter()
+            .map(|(name, command)| {
+                let command = command
+                    .as_str()
+                    .ok_or(ConfigValueError::InvalidType { possible_types: vec!["String"] })?;
+
+                Entry::new(name, command)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self)
+    }
+}
+
+impl Deref for ConfigEntries {
+    type Target = [Entry];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Deref for ConfigAliases {
+    type Target = [Entry];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use toml_edit::DocumentMut;
+
+    use super::*;
+
+    fn parse(toml: &str) -> DocumentMut {
+        toml.parse()
+            .expect("The TOML to be valid.")
+    }
+
+    #[test]
+    fn entries_have_a_name_and_a_quoted_command() {
+        let document = parse(
+            r#"
+            [[entries]]
+            name = "vpn-up"
+            description = "Connect to work"
+            command = "nmcli connection up 'Work VPN'"
+            "#,
+        );
+
+        let entries = ConfigEntries::try_from(document["entries"].clone()).unwrap();
+
+        assert_eq!(entries[0].name(), "vpn-up");
+        assert_eq!(entries[0].description(), Some("Connect to work"));
+        assert_eq!(entries[0].command(), ["nmcli", "connection", "up", "Work VPN"]);
+        assert_eq!(entries[0].icon(), None);
+    }
+
+    #[test]
+    fn aliases_map_names_to_commands() {
+        let document = parse("[aliases]\nweb = \"firefox --new-window\"\n");
+
+        let aliases = ConfigAliases::try_from(document["aliases"].clone()).unwrap();
+
+        assert_eq!(*aliases, [Entry::new("web", "firefox --new-window").unwrap()]);
+    }
+
+    #[test]
+    fn entries_without_a_name_or_command_fail() {
+        let document = parse("entries = [{ command = \"firefox\" }, { name = \"web\" }]");
+        assert!(ConfigEntries::try_from(document["entries"].clone()).is_err());
+
+        let document = parse("entries = [{ name = \"web\" }]");
+        assert!(ConfigEntries::try_from(document["entries"].clone()).is_err());
+
+        assert!(Entry::new("web", "'firefox").is_err());
+    }
+}
@@ -1,2 +1,4 @@
 pub mod args;
+pub mod entries;
 pub mod path;
+pub mod stdin;
@@ -0,0 +1,39 @@
+use std::io::{BufRead, Error as IoError};
+
+use log::info;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum StdinError {
+    #[error("A problem occurred while reading the entries from stdin.")]
+    IoError(#[from] IoError),
+}
+
+/// Reads an entry for each non empty line, in order, like dmenu.
+pub fn get_stdin_entries(input: impl BufRead) -> Result<Vec<String>, StdinError> {
+    let mut entries = Vec::new();
+
+    for line in input.lines() {
+        let line = line?;
+
+        if !line.is_empty() {
+            entries.push(line);
+        }
+    }
+
+    info!("Loaded {} stdin entries", entries.len());
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_lines_are_skipped() {
+        let entries = get_stdin_entries("b\n\na\r\n".as_bytes()).unwrap();
+
+        assert_eq!(entries, ["b", "a"]);
+    }
+}
@@ -0,0 +1,37 @@
+use sdl2::event::Event;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, RenderTarget};
+
+use super::texture_cache::TextureCache;
+use crate::menu::state::{MenuOutcome, MenuState};
+use crate::utils::errors::GenericComponentError;
+
+/// Whether a component used an event, events nobody
+/// consumes are handled by the frontend, keybindings
+/// for example.
+#[derive(Debug, PartialEq, Eq)]
+pub enum EventResult {
+    Consumed(MenuOutcome),
+    Ignored,
+}
+
+/// A part of the menu window, components read and change the
+/// `MenuState` but only decide how it's drawn and where.
+pub trait Component<T: RenderTarget> {
+    /// Places the component, called after every event
+    /// so it can follow changes of the state.
+    fn layout(&mut self, bounds: Rect, state: &mut MenuState) -> Result<(), GenericComponentError>;
+
+    fn handle_event(
+        &mut self,
+        event: &Event,
+        state: &mut MenuState,
+    ) -> Result<EventResult, GenericComponentError>;
+
+    fn draw(
+        &self,
+        state: &MenuState,
+        renderer: &mut Canvas<T>,
+        texture_cache: &mut TextureCache<'_, T::Context>,
+    ) -> Result<(), GenericComponentError>;
+}
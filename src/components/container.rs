@@ -0,0 +1,223 @@
+use sdl2::event::Event;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, RenderTarget};
+
+use super::component::{Component, EventResult};
+use super::texture_cache::TextureCache;
+use crate::menu::state::MenuState;
+use crate::utils::errors::GenericComponentError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Horizontal,
+    Vertical,
+}
+
+/// How much of the container a child takes along its direction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Length {
+    Fixed(u32),
+    /// An equal part of what the fixed children leave.
+    Fill,
+}
+
+/// Places its children one after the other, the container
+/// is a component itself, so containers can be nested.
+pub struct Container<'c, T: RenderTarget> {
+    direction: Direction,
+    children: Vec<(Length, Box<dyn Component<T> + 'c>)>,
+}
+
+impl<'c, T: RenderTarget> Container<'c, T> {
+    #[inline]
+    pub fn new(direction: Direction) -> Self {
+        Self { direction, children: Vec::new() }
+    }
+
+    #[inline]
+    pub fn horizontal() -> Self {
+        Self::new(Direction::Horizontal)
+    }
+
+    #[inline]
+    pub fn vertical() -> Self {
+        Self::new(Direction::Vertical)
+    }
+
+    pub fn with(mut self, length: Length, child: impl Component<T> + 'c) -> Self {
+        self.children
+            .push((length, Box::new(child)));
+        self
+    }
+
+    // Splits the bounds between the children.
+    fn child_bounds(&self, bounds: Rect) -> Vec<Rect> {
+        let total = match self.direction {
+            Direction::Horizontal => bounds.width(),
+            Direction::Vertical => bounds.height(),
+        };
+
+        let fixed = self
+            .children
+            .iter()
+            .map(|(length, _)| match length {
+                Length::Fixed(length) => *length,
+                Length::Fill => 0,
+            })
+            .sum::<u32>();
+
+        let fills = self
+            .children
+            .iter()
+            .filter(|(length, _)| *length == Length::Fill)
+            .count() as u32;
+
+        let fill = total
+            .saturating_sub(fixed)
+            .checked_div(fills)
+            .unwrap_or(0);
+
+        let mut offset = 0;
+
+        self.children
+            .iter()
+            .map(|(length, _)| {
+                let length = match length {
+                    Length::Fixed(length) => (*length).min(total.saturating_sub(offset)),
+                    Length::Fill => fill,
+                };
+
+                let child = match self.direction {
+                    Direction::Horizontal => {
+                        Rect::new(bounds.x() + offset as i32, bounds.y(), length, bounds.height())
+                    },
+                    Direction::Vertical => {
+                        Rect::new(bounds.x(), bounds.y() + offset as i32, bounds.width(), length)
+                    },
+                };
+
+                offset += length;
+                child
+            })
+            .collect()
+    }
+}
+
+impl<T: RenderTarget> Component<T> for Container<'_, T> {
+    fn layout(&mut self, bounds: Rect, state: &mut MenuState) -> Result<(), GenericComponentError> {
+        let child_bounds = self.child_bounds(bounds);
+
+        for ((_, child), bounds) in self
+            .children
+            .iter_mut()
+            .zip(child_bounds)
+        {
+            child.layout(bounds, state)?;
+        }
+
+        Ok(())
+    }
+
+    fn handle_event(
+        &mut self,
+        event: &Event,
+        state: &mut MenuState,
+    ) -> Result<EventResult, GenericComponentError> {
+        for (_, child) in &mut self.children {
+            if let EventResult::Consumed(outcome) = child.handle_event(event, state)? {
+                return Ok(EventResult::Consumed(outcome));
+            }
+        }
+
+        Ok(EventResult::Ignored)
+    }
+
+    fn draw(
+        &self,
+        state: &MenuState,
+        renderer: &mut Canvas<T>,
+        texture_cache: &mut TextureCache<'_, T::Context>,
+    ) -> Result<(), GenericComponentError> {
+        for (_, child) in &self.children {
+            child.draw(state, renderer, texture_cache)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use sdl2::surface::Surface;
+
+    use super::*;
+
+    // Remembers where it was placed.
+    struct Probe(Rc<Cell<Option<Rect>>>);
+
+    impl Component<Surface<'static>> for Probe {
+        fn layout(&mut self, bounds: Rect, _: &mut MenuState) -> Result<(), GenericComponentError> {
+            self.0
+                .set(Some(bounds));
+            Ok(())
+        }
+
+        fn handle_event(
+            &mut self,
+            _: &Event,
+            _: &mut MenuState,
+        ) -> Result<EventResult, GenericComponentError> {
+            Ok(EventResult::Ignored)
+        }
+
+        fn draw(
+            &self,
+            _: &MenuState,
+            _: &mut Canvas<Surface<'static>>,
+            _: &mut TextureCache<'_, <Surface<'static> as RenderTarget>::Context>,
+        ) -> Result<(), GenericComponentError> {
+            Ok(())
+        }
+    }
+
+    fn probe() -> (Probe, Rc<Cell<Option<Rect>>>) {
+        let bounds = Rc::new(Cell::new(None));
+        (Probe(bounds.clone()), bounds)
+    }
+
+    #[test]
+    fn fixed_children_keep_their_length_and_fills_share_the_rest() {
+        let (first, first_bounds) = probe();
+        let (second, second_bounds) = probe();
+        let (third, third_bounds) = probe();
+
+        Container::horizontal()
+            .with(Length::Fill, first)
+            .with(Length::Fixed(100), second)
+            .with(Length::Fill, third)
+            .layout(Rect::new(10, 5, 500, 20), &mut MenuState::new(Vec::new()))
+            .expect("The layout to succeed.");
+
+        assert_eq!(first_bounds.get(), Some(Rect::new(10, 5, 200, 20)));
+        assert_eq!(second_bounds.get(), Some(Rect::new(210, 5, 100, 20)));
+        assert_eq!(third_bounds.get(), Some(Rect::new(310, 5, 200, 20)));
+    }
+
+    #[test]
+    fn vertical_containers_split_the_height() {
+        let (top, top_bounds) = probe();
+        let (bottom, bottom_bounds) = probe();
+
+        Container::vertical()
+            .with(Length::Fixed(20), top)
+            .with(Length::Fill, bottom)
+            .layout(Rect::new(0, 0, 300, 200), &mut MenuState::new(Vec::new()))
+            .expect("The layout to succeed.");
+
+        assert_eq!(top_bounds.get(), Some(Rect::new(0, 0, 300, 20)));
+        assert_eq!(bottom_bounds.get(), Some(Rect::new(0, 20, 300, 180)));
+    }
+}
@@ -0,0 +1,60 @@
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, RenderTarget};
+
+use super::texture_cache::TextureCache;
+use crate::utils::display_scale::scale_i32;
+use crate::utils::errors::GenericComponentError;
+use crate::utils::fonts::FontSet;
+
+/// The bar shown instead of the menu when the configuration
+/// couldn't be loaded, a red dot followed by the error.
+pub struct ErrorBar<'f> {
+    font: &'f FontSet<'f>,
+    message: String,
+
+    scale: f32,
+}
+
+impl<'f> ErrorBar<'f> {
+    pub const BACKGROUND_COLOR: Color = Color::RGB(20, 20, 20);
+
+    pub fn new(font: &'f FontSet<'f>, error: &str) -> Self {
+        Self {
+            font,
+            message: format!("{error} | Press <ESC> or <RETURN> to exit."),
+
+            scale: 1.0,
+        }
+    }
+
+    #[inline]
+    pub const fn set_scale(&mut self, scale: f32) {
+        self.scale = scale;
+    }
+
+    pub fn draw<T: RenderTarget>(
+        &self,
+        renderer: &mut Canvas<T>,
+        texture_cache: &mut TextureCache<'_, T::Context>,
+    ) -> Result<(), GenericComponentError> {
+        renderer.filled_circle(
+            scale_i32(10, self.scale) as i16,
+            scale_i32(10, self.scale) as i16,
+            scale_i32(5, self.scale) as i16,
+            Color::RED,
+        )?;
+
+        let text =
+            texture_cache.text::<GenericComponentError>(self.font, &self.message, Color::RED)?;
+
+        renderer.copy(
+            text.texture(),
+            None,
+            Some(Rect::new(scale_i32(25, self.scale), 0, text.width(), text.height())),
+        )?;
+
+        Ok(())
+    }
+}
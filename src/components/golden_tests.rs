@@ -0,0 +1,306 @@
+// Renders the components into a software canvas and compares the
+// result with the PNG images in `tests/golden`, so the layout can be
+// changed without a display.
+//
+// Run with `RMENU_BLESS=1` to write the images, the first time or
+// after an intended change, a missing image fails the test otherwise.
+
+use std::env::var_os;
+use std::fs::{File, create_dir_all};
+use std::io::BufWriter;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::surface::Surface;
+
+use super::component::Component;
+use super::container::{Container, Length};
+use super::error_bar::ErrorBar;
+use super::match_selector::pager::Pager;
+use super::prompt::Prompt;
+use super::text_input::TextInput;
+use super::texture_cache::TextureCache;
+use crate::config::keybindings::Action;
+use crate::menu::state::{MenuInput, MenuState};
+use crate::utils::fonts::{FontSet, load_default_font};
+
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 20;
+
+// The default configuration colors.
+const BACKGROUND_COLOR: Color = Color::RGB(0x29, 0x29, 0x29);
+const TEXT_COLOR: Color = Color::RGB(0xFF, 0xFF, 0xFF);
+const HIGHLIGHT_COLOR: Color = Color::RGB(0x66, 0x66, 0x66);
+const MATCH_COLOR: Color = Color::RGB(0xF0, 0xC6, 0x74);
+
+// How much a channel can change before a pixel counts as different,
+// so small anti aliasing differences between FreeType versions pass.
+const CHANNEL_TOLERANCE: u8 = 8;
+
+// SDL_ttf isn't thread safe and the tests run in parallel.
+static RENDER_LOCK: Mutex<()> = Mutex::new(());
+
+const PROGRAMS: &[&str] = &[
+    "alacritty",
+    "bash",
+    "chromium",
+    "cp",
+    "dmenu",
+    "file-roller",
+    "find",
+    "firefox",
+    "fish",
+    "gimp",
+    "git",
+    "htop",
+    "inkscape",
+    "kitty",
+    "less",
+    "ls",
+    "mpv",
+    "nautilus",
+    "nvim",
+    "pavucontrol",
+    "python3",
+    "rmenu",
+    "rsync",
+    "steam",
+    "thunderbird",
+    "vlc",
+    "wget",
+    "xterm",
+    "zathura",
+    "zsh",
+];
+
+fn font() -> FontSet<'static> {
+    FontSet::new(vec![load_default_font(14).expect("The default font to load.")])
+        .expect("A font set with one font.")
+}
+
+fn canvas() -> Canvas<Surface<'static>> {
+    let mut canvas = Surface::new(WIDTH, HEIGHT, PixelFormatEnum::ARGB8888)
+        .expect("The surface to be created.")
+        .into_canvas()
+        .expect("The software canvas to be created.");
+
+    canvas.set_draw_color(BACKGROUND_COLOR);
+    canvas.clear();
+
+    canvas
+}
+
+fn pager<'f>(font: &'f FontSet<'f>) -> Pager<'f> {
+    let mut pager = Pager::new(font);
+
+    pager.set_text_color(TEXT_COLOR);
+    pager.set_highlight_color(HIGHLIGHT_COLOR);
+    pager.set_highlighted_text_color(TEXT_COLOR);
+    pager.set_match_color(MATCH_COLOR);
+
+    pager
+}
+
+// Draws the input and the pager like the main loop does.
+fn render_menu(
+    prompt: Option<&str>,
+    input_text: &str,
+    programs: &[&str],
+    pages_forward: usize,
+) -> Vec<u8> {
+    let _lock = RENDER_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    let font = font();
+    let mut canvas = canvas();
+    let texture_creator = canvas.texture_creator();
+    let mut texture_cache = TextureCache::new(&texture_creator);
+
+    let mut state = MenuState::new(
+        programs
+            .iter()
+            .map(|program| program.to_string())
+            .collect(),
+    );
+    state.handle(MenuInput::Paste(input_text));
+
+    let mut input = TextInput::new(&font);
+    input.set_color(TEXT_COLOR);
+    input.set_selection_color(HIGHLIGHT_COLOR);
+
+    let mut root = Container::horizontal();
+    let mut prompt_width = 0;
+
+    if let Some(prompt) = prompt {
+        let prompt = Prompt::new(&font, prompt);
+        prompt_width = prompt
+            .width()
+            .expect("The prompt to be measured.");
+        root = root.with(Length::Fixed(prompt_width), prompt);
+    }
+
+    // The input takes the same share of the space after the prompt main gives it.
+    let mut root = root
+        .with(Length::Fixed(TextInput::width_beside_pager(WIDTH, prompt_width)), input)
+        .with(Length::Fill, pager(&font));
+
+    let bounds = Rect::new(0, 0, WIDTH, HEIGHT);
+    root.layout(bounds, &mut state)
+        .expect("The components to be laid out.");
+
+    for _ in 0..pages_forward {
+        state.handle(MenuInput::Action(Action::PageNext));
+    }
+
+    root.layout(bounds, &mut state)
+        .expect("The components to be laid out.");
+    root.draw(&state, &mut canvas, &mut texture_cache)
+        .expect("The components to be drawn.");
+
+    pixels(canvas)
+}
+
+fn pixels(canvas: Canvas<Surface<'static>>) -> Vec<u8> {
+    let surface = canvas
+        .into_surface()
+        .convert_format(PixelFormatEnum::RGBA32)
+        .expect("The surface to be converted to RGBA.");
+
+    let pitch = surface.pitch() as usize;
+    let row_length = WIDTH as usize * 4;
+
+    surface.with_lock(|bytes| {
+        bytes
+            .chunks(pitch)
+            .flat_map(|row| &row[..row_length])
+            .copied()
+            .collect()
+    })
+}
+
+fn assert_golden(name: &str, pixels: &[u8]) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{name}.png"));
+
+    if var_os("RMENU_BLESS").is_some() {
+        create_dir_all(
+            path.parent()
+                .expect("The golden path to have a parent."),
+        )
+        .expect("The golden directory to be created.");
+
+        let mut encoder = png::Encoder::new(
+            BufWriter::new(File::create(&path).expect("The golden image to be created.")),
+            WIDTH,
+            HEIGHT,
+        );
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(pixels))
+            .expect("The golden image to be written.");
+
+        return;
+    }
+
+    let Ok(file) = File::open(&path) else {
+        panic!("{} can't be read, run with RMENU_BLESS=1 to write it", path.display());
+    };
+
+    let decoder = png::Decoder::new(file);
+    let mut reader = decoder
+        .read_info()
+        .expect("The golden image to be a PNG.");
+    let mut expected = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut expected)
+        .expect("The golden image to be decoded.");
+
+    assert_eq!(
+        (info.width, info.height, info.color_type),
+        (WIDTH, HEIGHT, png::ColorType::Rgba),
+        "{name} has a different size or format than the golden image"
+    );
+
+    let different = expected
+        .chunks(4)
+        .zip(pixels.chunks(4))
+        .filter(|(expected, actual)| {
+            expected
+                .iter()
+                .zip(actual.iter())
+                .any(|(expected, actual)| expected.abs_diff(*actual) > CHANNEL_TOLERANCE)
+        })
+        .count();
+
+    assert_eq!(
+        different,
+        0,
+        "{name} differs from {} in {different} pixels, run with RMENU_BLESS=1 if it's intended",
+        path.display()
+    );
+}
+
+#[test]
+fn empty_input() {
+    assert_golden("empty_input", &render_menu(None, "", PROGRAMS, 0));
+}
+
+#[test]
+fn filtered_list() {
+    assert_golden("filtered_list", &render_menu(None, "fi", PROGRAMS, 0));
+}
+
+#[test]
+fn multiple_pages() {
+    let programs = PROGRAMS
+        .iter()
+        .chain(PROGRAMS)
+        .copied()
+        .collect::<Vec<_>>();
+
+    assert_golden("multiple_pages", &render_menu(None, "", &programs, 0));
+}
+
+#[test]
+fn selection_on_second_page() {
+    let programs = PROGRAMS
+        .iter()
+        .chain(PROGRAMS)
+        .copied()
+        .collect::<Vec<_>>();
+
+    assert_golden("selection_on_second_page", &render_menu(None, "", &programs, 1));
+}
+
+#[test]
+fn prompt_before_input() {
+    assert_golden("prompt_before_input", &render_menu(Some("Kill process:"), "fi", PROGRAMS, 0));
+}
+
+#[test]
+fn config_error() {
+    let _lock = RENDER_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    let font = font();
+    let mut canvas = canvas();
+    let texture_creator = canvas.texture_creator();
+    let mut texture_cache = TextureCache::new(&texture_creator);
+
+    canvas.set_draw_color(ErrorBar::BACKGROUND_COLOR);
+    canvas.clear();
+
+    ErrorBar::new(&font, "Invalid value for 'window_position', expected 'top' or 'bottom'")
+        .draw(&mut canvas, &mut texture_cache)
+        .expect("The error bar to be drawn.");
+
+    assert_golden("config_error", &pixels(canvas));
+}
@@ -1,16 +1,24 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
 use sdl2::pixels::Color;
-use sdl2::rect::Rect;
-use sdl2::render::Canvas;
-use sdl2::ttf::Font;
-use sdl2::video::Window;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{Canvas, RenderTarget};
+use sdl2::ttf::FontError;
 
+use crate::components::texture_cache::TextureCache;
 use crate::utils::errors::GenericComponentError;
+use crate::utils::fonts::FontSet;
 use crate::utils::vector_matrix::{Vector2, Vector2I, Vector2U};
 
 pub struct PagerItem<'f> {
-    font: &'f Font<'f, 'f>,
+    font: &'f FontSet<'f>,
     text: String,
 
+    // Drawn dimmed after the text, and an image before it.
+    description: Option<String>,
+    icon: Option<PathBuf>,
+
     height: u32,
     padding: Vector2I,
 
@@ -18,15 +26,25 @@ pub struct PagerItem<'f> {
     highlight_color: Color,
     highlighted_text_color: Color,
 
+    // The matched parts of the text and their color.
+    match_ranges: Vec<Range<usize>>,
+    match_color: Color,
+
+    // The color of the bar under marked items.
+    mark_color: Color,
+
     position: Vector2I,
 }
 
 impl<'f> PagerItem<'f> {
-    pub(crate) fn new(font: &'f Font) -> Self {
+    pub(crate) fn new(font: &'f FontSet<'f>) -> Self {
         Self {
             font,
             text: " ".into(),
 
+            description: None,
+            icon: None,
+
             height: 0,
             padding: Vector2::new(0, 0),
 
@@ -34,6 +52,11 @@ impl<'f> PagerItem<'f> {
             highlight_color: Color::BLUE,
             highlighted_text_color: Color::WHITE,
 
+            match_ranges: Vec::new(),
+            match_color: Color::WHITE,
+
+            mark_color: Color::WHITE,
+
             position: Vector2::new(0, 0),
         }
     }
@@ -66,6 +89,16 @@ impl<'f> PagerItem<'f> {
         self.text = text.to_string();
     }
 
+    #[inline]
+    pub fn set_description(&mut self, description: Option<&str>) {
+        self.description = description.map(str::to_string);
+    }
+
+    #[inline]
+    pub fn set_icon(&mut self, icon: Option<&Path>) {
+        self.icon = icon.map(Path::to_path_buf);
+    }
+
     #[inline]
     pub const fn set_text_color(&mut self, color: Color) {
         self.text_color = color;
@@ -81,51 +114,208 @@ impl<'f> PagerItem<'f> {
         self.highlighted_text_color = color;
     }
 
+    #[inline]
+    pub fn set_match_ranges(&mut self, ranges: &[Range<usize>]) {
+        self.match_ranges = ranges.to_vec();
+    }
+
+    #[inline]
+    pub const fn set_match_color(&mut self, color: Color) {
+        self.match_color = color;
+    }
+
+    #[inline]
+    pub const fn set_mark_color(&mut self, color: Color) {
+        self.mark_color = color;
+    }
+
+    // Splits the text in runs that are or aren't part of a match.
+    fn runs(&self) -> Vec<(Range<usize>, bool)> {
+        let mut runs = Vec::new();
+        let mut offset = 0;
+
+        for range in &self.match_ranges {
+            if range.start > offset {
+                runs.push((offset..range.start, false));
+            }
+
+            runs.push((range.clone(), true));
+            offset = range.end;
+        }
+
+        if offset < self.text.len() {
+            runs.push((offset..self.text.len(), false));
+        }
+
+        runs
+    }
+
+    // Where the text starts and the width of everything, the icon is
+    // a square as high as the text, separated by half of that.
+    fn content_layout(&self) -> Result<(u32, u32), FontError> {
+        let (text_width, text_height) = self
+            .font
+            .size_of(&self.text)?;
+        let gap = text_height / 2;
+
+        let text_x = if self.icon.is_some() { text_height + gap } else { 0 };
+
+        let description_width = match &self.description {
+            Some(description) => {
+                gap + self
+                    .font
+                    .size_of(description)?
+                    .0
+            },
+            None => 0,
+        };
+
+        Ok((text_x, text_x + text_width + description_width))
+    }
+
     #[inline]
     pub fn get_size(&mut self) -> Result<Vector2U, GenericComponentError> {
-        Ok(self
+        let (_, width) = self.content_layout()?;
+        let (_, height) = self
             .font
-            .size_of(&self.text)
-            .map(|s| s.into())?)
+            .size_of(&self.text)?;
+
+        Ok(Vector2::new(width, height))
     }
 
-    pub fn draw(
+    pub fn contains_point(&self, point: Point) -> Result<bool, GenericComponentError> {
+        let (_, content_width) = self.content_layout()?;
+
+        Ok(Rect::new(
+            self.position.x(),
+            self.position.y(),
+            content_width + self.padding.x() as u32,
+            self.height.max(1),
+        )
+        .contains_point(point))
+    }
+
+    pub fn draw<T: RenderTarget>(
         &self,
-        renderer: &mut Canvas<Window>,
+        renderer: &mut Canvas<T>,
+        texture_cache: &mut TextureCache<'_, T::Context>,
         selected: bool,
+        marked: bool,
     ) -> Result<(), GenericComponentError> {
-        let texture_creator = renderer.texture_creator();
-        let text_surface = self
+        let (_, text_height) = self
             .font
-            .render(&self.text)
-            .blended(if selected { self.highlighted_text_color } else { self.text_color })?;
-        let texture = texture_creator.create_texture_from_surface(&text_surface)?;
+            .size_of(&self.text)?;
+        let (text_offset, content_width) = self.content_layout()?;
+
+        let prev_draw_color = renderer.draw_color();
 
         if selected {
-            let prev_draw_color = renderer.draw_color();
             renderer.set_draw_color(self.highlight_color);
 
             renderer.fill_rect(Rect::new(
                 self.position.x(),
                 self.position.y(),
-                text_surface.width() + self.padding.x() as u32,
-                text_surface.height() + self.padding.y() as u32 + self.height,
+                content_width + self.padding.x() as u32,
+                text_height + self.padding.y() as u32 + self.height,
+            ))?;
+        }
+
+        if marked {
+            let mark_height = (self.height / 10).max(2);
+
+            renderer.set_draw_color(self.mark_color);
+
+            renderer.fill_rect(Rect::new(
+                self.position.x(),
+                self.position.y() + self.height as i32 - mark_height as i32,
+                content_width + self.padding.x() as u32,
+                mark_height,
             ))?;
+        }
 
-            renderer.set_draw_color(prev_draw_color);
+        renderer.set_draw_color(prev_draw_color);
+
+        let text_color = if selected { self.highlighted_text_color } else { self.text_color };
+        let content_x = self.position.x() + self.padding.x() / 2;
+        let text_x = content_x + text_offset as i32;
+        let center_y = self.position.y() + self.padding.y() / 2 + (self.height / 2) as i32;
+
+        if let Some(icon) = &self.icon {
+            let icon = texture_cache.image::<GenericComponentError>(icon)?;
+
+            renderer.copy(
+                icon.texture(),
+                None,
+                Some(Rect::new(
+                    content_x,
+                    center_y - (text_height / 2) as i32,
+                    text_height,
+                    text_height,
+                )),
+            )?;
         }
 
-        renderer.copy(
-            &texture,
-            None,
-            Some(Rect::new(
-                self.position.x() + self.padding.x() / 2,
-                self.position.y() + self.padding.y() / 2 + (self.height / 2) as i32
-                    - (text_surface.height() / 2) as i32,
-                text_surface.width(),
-                text_surface.height(),
-            )),
-        )?;
+        for (range, matched) in self.runs() {
+            let run_x = if range.start == 0 {
+                text_x
+            } else {
+                text_x
+                    + self
+                        .font
+                        .size_of(&self.text[..range.start])?
+                        .0 as i32
+            };
+
+            let run = texture_cache.text::<GenericComponentError>(
+                self.font,
+                &self.text[range],
+                if matched { self.match_color } else { text_color },
+            )?;
+
+            renderer.copy(
+                run.texture(),
+                None,
+                Some(Rect::new(
+                    run_x,
+                    center_y - (run.height() / 2) as i32,
+                    run.width(),
+                    run.height(),
+                )),
+            )?;
+        }
+
+        if let Some(description) = &self.description {
+            let description_x = text_x
+                + self
+                    .font
+                    .size_of(&self.text)?
+                    .0 as i32
+                + (text_height / 2) as i32;
+
+            // Dimmed, so the name stands out.
+            let description_color = Color::RGB(
+                (text_color.r as f32 * 0.7) as u8,
+                (text_color.g as f32 * 0.7) as u8,
+                (text_color.b as f32 * 0.7) as u8,
+            );
+
+            let description = texture_cache.text::<GenericComponentError>(
+                self.font,
+                description,
+                description_color,
+            )?;
+
+            renderer.copy(
+                description.texture(),
+                None,
+                Some(Rect::new(
+                    description_x,
+                    center_y - (description.height() / 2) as i32,
+                    description.width(),
+                    description.height(),
+                )),
+            )?;
+        }
 
         Ok(())
     }
@@ -1,3 +1,2 @@
-pub mod cursor;
 pub mod item;
 pub mod pager;
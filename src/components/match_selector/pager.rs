@@ -1,84 +1,106 @@
-use std::cmp::min;
-use std::ops::Sub;
-use std::ptr::eq as ptr_eq;
-
-use sdl2::keyboard::Keycode;
+use sdl2::event::Event;
+use sdl2::mouse::{MouseButton, MouseWheelDirection};
 use sdl2::pixels::Color;
-use sdl2::rect::Rect;
-use sdl2::render::Canvas;
-use sdl2::ttf::Font;
-use sdl2::video::Window;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{Canvas, RenderTarget};
 
-use super::cursor::PagerCursor;
 use super::item::PagerItem;
+use crate::components::component::{Component, EventResult};
+use crate::components::texture_cache::TextureCache;
+use crate::config::keybindings::Action;
+use crate::config::types::Matcher;
+use crate::menu::state::{CandidateDetails, MenuInput, MenuOutcome, MenuState};
+use crate::utils::display_scale::{scale_i32, scale_u32};
 use crate::utils::errors::GenericComponentError;
-use crate::utils::vector_matrix::{Vector2, Vector2I, Vector2U};
+use crate::utils::fonts::FontSet;
+use crate::utils::vector_matrix::Vector2;
 
-pub struct Pager<'f> {
-    provided_entries: Vec<String>,
-    computed_entries: Vec<Vec<PagerItem<'f>>>,
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PageArrow {
+    Previous,
+    Next,
+}
 
-    caret_position: usize,
-    last_matched: Option<String>,
+/// Lays out the menu matches in pages that fit its
+/// width and draws the page of the selected match.
+pub struct Pager<'f> {
+    pages: Vec<Vec<PagerItem<'f>>>,
+    laid_out_generation: Option<u64>,
 
-    font: &'f Font<'f, 'f>,
+    font: &'f FontSet<'f>,
     text_color: Color,
 
     highlight_color: Color,
     highlighted_text_color: Color,
+    match_color: Color,
+    mark_color: Color,
+    urgent_color: Color,
 
     rect: Rect,
+    scale: f32,
+
+    // How many clicks on an entry start it.
+    launch_clicks: u8,
 }
 
 impl<'f> Pager<'f> {
-    pub fn new(mut entries: Vec<String>, font: &'f Font) -> Self {
-        entries.sort_by_key(|e| e.to_lowercase());
-
+    pub fn new(font: &'f FontSet<'f>) -> Self {
         Self {
-            computed_entries: Vec::with_capacity(entries.len()),
-            provided_entries: entries,
-
-            caret_position: 0,
-            last_matched: None,
+            pages: Vec::new(),
+            laid_out_generation: None,
 
             font,
             text_color: Color::WHITE,
 
             highlight_color: Color::WHITE,
             highlighted_text_color: Color::WHITE,
+            match_color: Color::WHITE,
+            mark_color: Color::WHITE,
+            urgent_color: Color::RED,
 
             rect: Rect::new(0, 0, 0, 0),
-        }
-    }
+            scale: 1.0,
 
-    pub fn compute_text(&mut self, text: &str) -> Result<(), GenericComponentError> {
-        if let Some(last_matched) = &self.last_matched {
-            if last_matched == text {
-                return Ok(());
-            }
+            launch_clicks: 2,
         }
+    }
 
-        self.last_matched = Some(text.to_string());
-        self.computed_entries
-            .clear();
+    // Splits the matches in pages, and tells the
+    // state how long the pages are.
+    fn paginate(&mut self, state: &mut MenuState) -> Result<(), GenericComponentError> {
+        self.pages.clear();
 
-        const RIGHT_PAD: u32 = 150;
-        const LEFT_PAD: i32 = 10;
+        let right_pad = self.right_pad();
+        let left_pad = scale_i32(10, self.scale);
+        let item_padding = scale_i32(20, self.scale);
 
         let mut x_offset = 0;
         let mut current_page = Vec::new();
-        for entry_text in &self.provided_entries {
-            if !entry_text.starts_with(text) {
-                continue;
-            }
-
-            let mut entry = PagerItem::new(&self.font);
+        for (index, entry_text) in state
+            .matches()
+            .enumerate()
+        {
+            let mut entry = PagerItem::new(self.font);
             entry.set_text(entry_text);
+            entry.set_match_ranges(state.highlights(index));
+
+            let details = state.details(index);
+
+            if let Some(details) = details {
+                entry.set_description(details.description());
+                entry.set_icon(details.icon());
+            }
 
             let entry_size = entry.get_size()?;
 
-            if entry_size.x() + x_offset > self.rect.width() - RIGHT_PAD {
-                self.computed_entries
+            if entry_size.x() + x_offset
+                > self
+                    .rect
+                    .width()
+                    .saturating_sub(right_pad)
+                && !current_page.is_empty()
+            {
+                self.pages
                     .push(current_page);
 
                 current_page = Vec::new();
@@ -86,77 +108,178 @@ impl<'f> Pager<'f> {
             }
 
             entry.set_position(Vector2::new(
-                LEFT_PAD + self.rect.x() + x_offset as i32,
+                left_pad + self.rect.x() + x_offset as i32,
                 self.rect.y(),
             ));
             entry.set_highlighted_text_color(self.highlighted_text_color);
             entry.set_highlight_color(self.highlight_color);
-            entry.set_text_color(self.text_color);
-            entry.set_padding(Vector2::new(20, 0));
+            entry.set_text_color(if details.is_some_and(CandidateDetails::urgent) {
+                self.urgent_color
+            } else {
+                self.text_color
+            });
+            entry.set_match_color(self.match_color);
+            entry.set_mark_color(self.mark_color);
+            entry.set_padding(Vector2::new(item_padding, 0));
             entry.set_height(self.rect.height());
 
             current_page.push(entry);
 
-            x_offset += entry_size.x() as u32 + 20;
+            x_offset += entry_size.x() as u32 + item_padding as u32;
         }
 
         if !current_page.is_empty() {
-            self.computed_entries
+            self.pages
                 .push(current_page);
         }
 
-        self.caret_position = 0;
+        state.set_page_lengths(
+            self.pages
+                .iter()
+                .map(|page| page.len())
+                .collect(),
+        );
+        self.laid_out_generation = Some(state.generation());
 
         Ok(())
     }
 
-    pub fn advance_caret(&mut self) {
-        self.caret_position = min(
-            self.caret_position + 1,
-            self.computed_entries
-                .iter()
-                .map(|page| page.len())
-                .sum::<usize>()
-                .sub(1),
-        );
+    // The page with the selected match and its index.
+    fn selected_page(&self, state: &MenuState) -> Option<(usize, &[PagerItem<'f>])> {
+        let page_index = state.page_of(state.selected_index()?);
+
+        self.pages
+            .get(page_index)
+            .map(|page| (page_index, page.as_slice()))
     }
 
-    #[inline]
-    pub fn retreat_caret(&mut self) {
-        if self.caret_position > 0 {
-            self.caret_position -= 1;
+    // The index of the match under a point of
+    // the current page, if there is one there.
+    fn entry_at(
+        &self,
+        state: &MenuState,
+        point: Point,
+    ) -> Result<Option<usize>, GenericComponentError> {
+        let Some((page_index, page)) = self.selected_page(state) else { return Ok(None) };
+
+        for (item_index, entry) in page
+            .iter()
+            .enumerate()
+        {
+            if entry.contains_point(point)? {
+                return Ok(Some(
+                    state
+                        .page_range(page_index)
+                        .start
+                        + item_index,
+                ));
+            }
         }
-    }
 
-    #[inline]
-    pub const fn is_caret_at_start(&self) -> bool {
-        self.caret_position == 0
+        Ok(None)
     }
 
-    #[inline]
-    pub(super) const fn caret_position(&self) -> usize {
-        self.caret_position
-    }
+    // Which of the page arrows drawn by `draw` is
+    // under a point, if the arrow is being shown.
+    fn arrow_at(&self, state: &MenuState, point: Point) -> Option<PageArrow> {
+        let (page_index, _) = self.selected_page(state)?;
 
-    #[inline]
-    pub(super) const fn computed_entries(&self) -> &Vec<Vec<PagerItem>> {
-        &self.computed_entries
+        if !self
+            .rect
+            .contains_point(point)
+        {
+            return None;
+        }
+
+        if page_index > 0 && point.x() < self.rect.x() + scale_i32(10, self.scale) {
+            return Some(PageArrow::Previous);
+        }
+
+        if page_index + 1 < self.pages.len()
+            && point.x() >= self.rect.x() + self.rect.width() as i32 - scale_i32(25, self.scale)
+        {
+            return Some(PageArrow::Next);
+        }
+
+        None
     }
 
+    // The space at the right of the pages, for the arrow and the matcher status.
     #[inline]
-    pub fn get_selected_entry(&self) -> Option<PagerCursor> {
-        PagerCursor::from_instance(self)
+    fn right_pad(&self) -> u32 {
+        scale_u32(150, self.scale)
     }
 
-    #[inline]
-    pub fn keycode_interaction(&mut self, keycode: Keycode) {
-        match keycode {
-            Keycode::Right => self.advance_caret(),
+    // Whether the input is a regex, or why it isn't a valid one, right
+    // aligned before the next page arrow so the matches stay visible.
+    fn draw_matcher_status<T: RenderTarget>(
+        &self,
+        state: &MenuState,
+        renderer: &mut Canvas<T>,
+        texture_cache: &mut TextureCache<'_, T::Context>,
+        color: Color,
+    ) -> Result<(), GenericComponentError> {
+        let (status, color) = match (state.matcher(), state.regex_error()) {
+            (Matcher::Text, _) => return Ok(()),
+            (Matcher::Regex, None) => ("regex", color),
+            (Matcher::Regex, Some(error)) => (error, Color::RED),
+        };
+
+        let status = texture_cache.text::<GenericComponentError>(self.font, status, color)?;
+
+        let arrow_width = scale_u32(25, self.scale);
+        let area = Rect::new(
+            self.rect.x() + self.rect.width() as i32 - self.right_pad() as i32,
+            self.rect.y(),
+            self.right_pad() - arrow_width,
+            self.rect.height(),
+        );
+        let area_end = area.x() + area.width() as i32;
+
+        // Long errors are cut at the left of the area.
+        let prev_clip = renderer.clip_rect();
+        renderer.set_clip_rect(area);
+        renderer.copy(
+            status.texture(),
+            None,
+            Some(Rect::new(
+                area_end - status.width() as i32,
+                self.rect.y() + (self.rect.height() / 2) as i32 - (status.height() / 2) as i32,
+                status.width(),
+                status.height(),
+            )),
+        )?;
+        renderer.set_clip_rect(prev_clip);
+
+        Ok(())
+    }
 
-            Keycode::Left => self.retreat_caret(),
+    // Why the accepted entry couldn't be started, in place of the
+    // pages since it's usually a typo in what's typed.
+    fn draw_launch_error<T: RenderTarget>(
+        &self,
+        error: &str,
+        renderer: &mut Canvas<T>,
+        texture_cache: &mut TextureCache<'_, T::Context>,
+    ) -> Result<(), GenericComponentError> {
+        let error = texture_cache.text::<GenericComponentError>(self.font, error, Color::RED)?;
+
+        // Long errors are cut at the right of the pager.
+        let prev_clip = renderer.clip_rect();
+        renderer.set_clip_rect(self.rect);
+        renderer.copy(
+            error.texture(),
+            None,
+            Some(Rect::new(
+                self.rect.x(),
+                self.rect.y() + (self.rect.height() / 2) as i32 - (error.height() / 2) as i32,
+                error.width(),
+                error.height(),
+            )),
+        )?;
+        renderer.set_clip_rect(prev_clip);
 
-            _ => {},
-        }
+        Ok(())
     }
 
     #[inline]
@@ -170,28 +293,120 @@ impl<'f> Pager<'f> {
     }
 
     #[inline]
-    pub fn set_position(&mut self, position: Vector2I) {
-        self.rect
-            .set_x(position.x());
-        self.rect
-            .set_y(position.y());
+    pub const fn set_highlighted_text_color(&mut self, color: Color) {
+        self.highlighted_text_color = color;
     }
 
     #[inline]
-    pub fn set_size(&mut self, size: Vector2U) {
-        self.rect
-            .set_width(size.x());
-        self.rect
-            .set_height(size.y());
+    pub const fn set_match_color(&mut self, color: Color) {
+        self.match_color = color;
     }
 
     #[inline]
-    pub const fn set_highlighted_text_color(&mut self, color: Color) {
-        self.highlighted_text_color = color;
+    pub const fn set_mark_color(&mut self, color: Color) {
+        self.mark_color = color;
     }
 
-    pub fn draw(&self, renderer: &mut Canvas<Window>) -> Result<(), GenericComponentError> {
-        let Some(selected) = self.get_selected_entry() else { return Ok(()) };
+    #[inline]
+    pub const fn set_scale(&mut self, scale: f32) {
+        self.scale = scale;
+    }
+
+    /// The text color of the urgent matches.
+    #[inline]
+    pub const fn set_urgent_color(&mut self, color: Color) {
+        self.urgent_color = color;
+    }
+
+    #[inline]
+    pub const fn set_launch_clicks(&mut self, clicks: u8) {
+        self.launch_clicks = clicks;
+    }
+}
+
+impl<T: RenderTarget> Component<T> for Pager<'_> {
+    // The matches are only split in pages again
+    // when they changed or the bounds did.
+    fn layout(&mut self, bounds: Rect, state: &mut MenuState) -> Result<(), GenericComponentError> {
+        if self.rect == bounds && self.laid_out_generation == Some(state.generation()) {
+            return Ok(());
+        }
+
+        self.rect = bounds;
+        self.paginate(state)
+    }
+
+    fn handle_event(
+        &mut self,
+        event: &Event,
+        state: &mut MenuState,
+    ) -> Result<EventResult, GenericComponentError> {
+        if state.in_args() {
+            return Ok(EventResult::Ignored);
+        }
+
+        let outcome = match *event {
+            Event::MouseButtonDown {
+                mouse_btn: MouseButton::Left,
+                x,
+                y,
+                clicks,
+                ..
+            } if self
+                .rect
+                .contains_point(Point::new(x, y)) =>
+            {
+                let point = Point::new(x, y);
+
+                match self.arrow_at(state, point) {
+                    Some(PageArrow::Previous) => state.handle(MenuInput::Action(Action::PagePrev)),
+                    Some(PageArrow::Next) => state.handle(MenuInput::Action(Action::PageNext)),
+
+                    None => match self.entry_at(state, point)? {
+                        Some(index) => {
+                            state.handle(MenuInput::Select(index));
+
+                            if clicks >= self.launch_clicks {
+                                state.handle(MenuInput::Action(Action::Accept))
+                            } else {
+                                MenuOutcome::Continue
+                            }
+                        },
+
+                        None => MenuOutcome::Continue,
+                    },
+                }
+            },
+
+            Event::MouseWheel { y, direction, .. } => {
+                let y = if direction == MouseWheelDirection::Flipped { -y } else { y };
+
+                match y {
+                    1.. => state.handle(MenuInput::Action(Action::Prev)),
+                    ..0 => state.handle(MenuInput::Action(Action::Next)),
+                    0 => MenuOutcome::Continue,
+                }
+            },
+
+            _ => return Ok(EventResult::Ignored),
+        };
+
+        Ok(EventResult::Consumed(outcome))
+    }
+
+    fn draw(
+        &self,
+        state: &MenuState,
+        renderer: &mut Canvas<T>,
+        texture_cache: &mut TextureCache<'_, T::Context>,
+    ) -> Result<(), GenericComponentError> {
+        if let Some(error) = state.launch_error() {
+            return self.draw_launch_error(error, renderer, texture_cache);
+        }
+
+        if state.in_args() {
+            return Ok(());
+        }
 
         let arrow_colors = Color::RGB(
             (self.text_color.r as f32 * 0.9) as u8,
@@ -199,50 +414,58 @@ impl<'f> Pager<'f> {
             (self.text_color.b as f32 * 0.9) as u8,
         );
 
-        let texture_creator = renderer.texture_creator();
+        self.draw_matcher_status(state, renderer, texture_cache, arrow_colors)?;
 
-        if selected.page_index() > 0 {
-            let arrow_left = self
-                .font
-                .render_char('<')
-                .blended(arrow_colors)?;
+        let Some((page_index, page)) = self.selected_page(state) else { return Ok(()) };
 
-            let arrow_left_texture = texture_creator.create_texture_from_surface(arrow_left)?;
+        if page_index > 0 {
+            let arrow_left =
+                texture_cache.text::<GenericComponentError>(self.font, "<", arrow_colors)?;
 
             renderer.copy(
-                &arrow_left_texture,
+                arrow_left.texture(),
                 None,
-                Some(Rect::new(self.rect.x(), self.rect.y(), 5, self.rect.height())),
+                Some(Rect::new(
+                    self.rect.x(),
+                    self.rect.y(),
+                    scale_u32(5, self.scale),
+                    self.rect.height(),
+                )),
             )?;
         }
 
-        if selected.page_index()
-            < self
-                .computed_entries
-                .len()
-                - 1
-        {
-            let arrow_right = self
-                .font
-                .render_char('>')
-                .blended(arrow_colors)?;
-
-            let arrow_right_texture = texture_creator.create_texture_from_surface(arrow_right)?;
+        if page_index + 1 < self.pages.len() {
+            let arrow_right =
+                texture_cache.text::<GenericComponentError>(self.font, ">", arrow_colors)?;
 
             renderer.copy(
-                &arrow_right_texture,
+                arrow_right.texture(),
                 None,
                 Some(Rect::new(
-                    self.rect.x() + self.rect.width() as i32 - 20,
+                    self.rect.x() + self.rect.width() as i32 - scale_i32(20, self.scale),
                     self.rect.y(),
-                    5,
+                    scale_u32(5, self.scale),
                     self.rect.height(),
                 )),
             )?;
         }
 
-        for entry in selected.page() {
-            entry.draw(renderer, ptr_eq(entry, selected.item()))?;
+        let page_start = state
+            .page_range(page_index)
+            .start;
+
+        for (item_index, entry) in page
+            .iter()
+            .enumerate()
+        {
+            let index = page_start + item_index;
+
+            entry.draw(
+                renderer,
+                texture_cache,
+                state.selected_index() == Some(index),
+                state.is_marked(index),
+            )?;
         }
 
         Ok(())
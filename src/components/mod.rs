@@ -1,2 +1,11 @@
+pub mod component;
+pub mod container;
+pub mod error_bar;
 pub mod match_selector;
+pub mod mode_bar;
+pub mod prompt;
 pub mod text_input;
+pub mod texture_cache;
+
+#[cfg(test)]
+mod golden_tests;
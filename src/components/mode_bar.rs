@@ -0,0 +1,175 @@
+use sdl2::event::Event;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{Canvas, RenderTarget};
+
+use super::component::{Component, EventResult};
+use super::texture_cache::TextureCache;
+use crate::menu::state::{MenuOutcome, MenuState};
+use crate::utils::display_scale::scale_u32;
+use crate::utils::errors::GenericComponentError;
+use crate::utils::fonts::FontSet;
+
+/// The names of the modes of the menu, the one shown is
+/// highlighted and clicking a name shows that mode.
+pub struct ModeBar<'f> {
+    font: &'f FontSet<'f>,
+    names: Vec<String>,
+    active: usize,
+
+    text_color: Color,
+    highlight_color: Color,
+    highlighted_text_color: Color,
+
+    bounds: Rect,
+    scale: f32,
+}
+
+impl<'f> ModeBar<'f> {
+    pub fn new(font: &'f FontSet<'f>, names: Vec<String>) -> Self {
+        Self {
+            font,
+            names,
+            active: 0,
+
+            text_color: Color::WHITE,
+            highlight_color: Color::BLUE,
+            highlighted_text_color: Color::WHITE,
+
+            bounds: Rect::new(0, 0, 0, 0),
+            scale: 1.0,
+        }
+    }
+
+    #[inline]
+    pub const fn set_active(&mut self, active: usize) {
+        self.active = active;
+    }
+
+    #[inline]
+    pub const fn set_text_color(&mut self, color: Color) {
+        self.text_color = color;
+    }
+
+    #[inline]
+    pub const fn set_highlight_color(&mut self, color: Color) {
+        self.highlight_color = color;
+    }
+
+    #[inline]
+    pub const fn set_highlighted_text_color(&mut self, color: Color) {
+        self.highlighted_text_color = color;
+    }
+
+    #[inline]
+    pub const fn set_scale(&mut self, scale: f32) {
+        self.scale = scale;
+    }
+
+    #[inline]
+    fn padding(&self) -> u32 {
+        scale_u32(10, self.scale)
+    }
+
+    // The width of each name, with padding at both sides.
+    fn name_widths(&self) -> Result<Vec<u32>, GenericComponentError> {
+        self.names
+            .iter()
+            .map(|name| {
+                let (text_width, _) = self
+                    .font
+                    .size_of(name)?;
+
+                Ok(text_width + self.padding() * 2)
+            })
+            .collect()
+    }
+
+    /// The width the bar needs, all the names one after another.
+    pub fn width(&self) -> Result<u32, GenericComponentError> {
+        Ok(self
+            .name_widths()?
+            .into_iter()
+            .sum())
+    }
+
+    // The bounds of each name, from left to right.
+    fn name_bounds(&self) -> Result<Vec<Rect>, GenericComponentError> {
+        let mut x = self.bounds.x();
+
+        Ok(self
+            .name_widths()?
+            .into_iter()
+            .map(|width| {
+                let bounds = Rect::new(x, self.bounds.y(), width, self.bounds.height());
+                x += width as i32;
+                bounds
+            })
+            .collect())
+    }
+}
+
+impl<T: RenderTarget> Component<T> for ModeBar<'_> {
+    fn layout(&mut self, bounds: Rect, _: &mut MenuState) -> Result<(), GenericComponentError> {
+        self.bounds = bounds;
+        Ok(())
+    }
+
+    fn handle_event(
+        &mut self,
+        event: &Event,
+        _: &mut MenuState,
+    ) -> Result<EventResult, GenericComponentError> {
+        let Event::MouseButtonDown { mouse_btn: MouseButton::Left, x, y, .. } = *event else {
+            return Ok(EventResult::Ignored);
+        };
+
+        Ok(self
+            .name_bounds()?
+            .iter()
+            .position(|bounds| bounds.contains_point(Point::new(x, y)))
+            .map_or(EventResult::Ignored, |index| {
+                EventResult::Consumed(MenuOutcome::ShowMode(index))
+            }))
+    }
+
+    fn draw(
+        &self,
+        _: &MenuState,
+        renderer: &mut Canvas<T>,
+        texture_cache: &mut TextureCache<'_, T::Context>,
+    ) -> Result<(), GenericComponentError> {
+        for (index, (name, bounds)) in self
+            .names
+            .iter()
+            .zip(self.name_bounds()?)
+            .enumerate()
+        {
+            let active = index == self.active;
+
+            if active {
+                let prev_draw_color = renderer.draw_color();
+                renderer.set_draw_color(self.highlight_color);
+                renderer.fill_rect(bounds)?;
+                renderer.set_draw_color(prev_draw_color);
+            }
+
+            let text_color = if active { self.highlighted_text_color } else { self.text_color };
+            let text = texture_cache.text::<GenericComponentError>(self.font, name, text_color)?;
+
+            renderer.copy(
+                text.texture(),
+                None,
+                Some(Rect::new(
+                    bounds.x() + self.padding() as i32,
+                    bounds.y() + (bounds.height() / 2) as i32 - (text.height() / 2) as i32,
+                    text.width(),
+                    text.height(),
+                )),
+            )?;
+        }
+
+        Ok(())
+    }
+}
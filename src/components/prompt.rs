@@ -0,0 +1,111 @@
+use sdl2::event::Event;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, RenderTarget};
+
+use super::component::{Component, EventResult};
+use super::texture_cache::TextureCache;
+use crate::menu::state::MenuState;
+use crate::utils::display_scale::scale_u32;
+use crate::utils::errors::GenericComponentError;
+use crate::utils::fonts::FontSet;
+
+/// A label before the input, like dmenu's `-p`,
+/// drawn over its own background color.
+pub struct Prompt<'f> {
+    font: &'f FontSet<'f>,
+    text: String,
+
+    text_color: Color,
+    background_color: Color,
+
+    bounds: Rect,
+    scale: f32,
+}
+
+impl<'f> Prompt<'f> {
+    pub fn new(font: &'f FontSet<'f>, text: &str) -> Self {
+        Self {
+            font,
+            text: text.to_string(),
+
+            text_color: Color::WHITE,
+            background_color: Color::GRAY,
+
+            bounds: Rect::new(0, 0, 0, 0),
+            scale: 1.0,
+        }
+    }
+
+    #[inline]
+    pub const fn set_text_color(&mut self, color: Color) {
+        self.text_color = color;
+    }
+
+    #[inline]
+    pub const fn set_background_color(&mut self, color: Color) {
+        self.background_color = color;
+    }
+
+    #[inline]
+    pub const fn set_scale(&mut self, scale: f32) {
+        self.scale = scale;
+    }
+
+    #[inline]
+    fn padding(&self) -> u32 {
+        scale_u32(10, self.scale)
+    }
+
+    /// The width the prompt needs, its text with padding at both sides.
+    pub fn width(&self) -> Result<u32, GenericComponentError> {
+        let (text_width, _) = self
+            .font
+            .size_of(&self.text)?;
+
+        Ok(text_width + self.padding() * 2)
+    }
+}
+
+impl<T: RenderTarget> Component<T> for Prompt<'_> {
+    fn layout(&mut self, bounds: Rect, _: &mut MenuState) -> Result<(), GenericComponentError> {
+        self.bounds = bounds;
+        Ok(())
+    }
+
+    fn handle_event(
+        &mut self,
+        _: &Event,
+        _: &mut MenuState,
+    ) -> Result<EventResult, GenericComponentError> {
+        Ok(EventResult::Ignored)
+    }
+
+    fn draw(
+        &self,
+        _: &MenuState,
+        renderer: &mut Canvas<T>,
+        texture_cache: &mut TextureCache<'_, T::Context>,
+    ) -> Result<(), GenericComponentError> {
+        let prev_draw_color = renderer.draw_color();
+        renderer.set_draw_color(self.background_color);
+        renderer.fill_rect(self.bounds)?;
+        renderer.set_draw_color(prev_draw_color);
+
+        let text =
+            texture_cache.text::<GenericComponentError>(self.font, &self.text, self.text_color)?;
+
+        renderer.copy(
+            text.texture(),
+            None,
+            Some(Rect::new(
+                self.bounds.x() + self.padding() as i32,
+                self.bounds.y() + (self.bounds.height() / 2) as i32 - (text.height() / 2) as i32,
+                text.width(),
+                text.height(),
+            )),
+        )?;
+
+        Ok(())
+    }
+}
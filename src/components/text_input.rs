@@ -1,195 +1,238 @@
-use std::cmp::min;
+use std::borrow::Cow;
 
-use sdl2::keyboard::Keycode;
+use sdl2::event::Event;
+use sdl2::mouse::MouseButton;
 use sdl2::pixels::Color;
-use sdl2::rect::Rect;
-use sdl2::render::{Canvas, TextureCreator, TextureValueError};
-use sdl2::ttf::{Font, FontError};
-use sdl2::video::{Window, WindowContext};
-use thiserror::Error;
-
-use crate::utils::keycode_to_char::keycode_to_char;
-use crate::utils::vector_matrix::Vector2I;
-
-#[derive(Error, Debug)]
-pub enum TextInputError {
-    #[error("A font error occurred\n- {0:#}")]
-    Font(#[from] FontError),
-
-    #[error("A texture error ocurred\n- {0:#}")]
-    Texture(#[from] TextureValueError),
-
-    #[error("A SDL error ocurred\n- {0}")]
-    Sdl(String),
-}
-
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{Canvas, RenderTarget};
+
+use super::component::{Component, EventResult};
+use super::texture_cache::TextureCache;
+use crate::menu::state::{MenuInput, MenuOutcome, MenuState};
+use crate::utils::display_scale::scale_u32;
+use crate::utils::errors::GenericComponentError;
+use crate::utils::fonts::FontSet;
+use crate::utils::misc::is_shift_held;
+
+/// Draws the menu line editor, the text,
+/// its selection and the caret.
 pub struct TextInput<'f> {
-    caret_position: u16,
-    buffer: Vec<char>,
-
-    font: &'f Font<'f, 'f>,
+    font: &'f FontSet<'f>,
     text_color: Color,
+    selection_color: Color,
+
+    // Drawn instead of each character of secret input,
+    // nothing at all is drawn without one.
+    mask: Option<char>,
+
+    bounds: Rect,
+    scale: f32,
 
-    position: Vector2I,
+    // Whether a mouse selection is being dragged.
+    selecting: bool,
 }
 
 impl<'f> TextInput<'f> {
     #[inline]
-    pub fn new(font: &'f Font) -> Self {
+    pub fn new(font: &'f FontSet<'f>) -> Self {
         Self {
-            caret_position: 0,
-            buffer: Vec::new(),
-
             font,
             text_color: Color::WHITE,
+            selection_color: Color::GRAY,
+
+            mask: Some('•'),
+
+            bounds: Rect::new(0, 0, 0, 0),
+            scale: 1.0,
 
-            position: Vector2I::new(0, 0),
+            selecting: false,
         }
     }
 
+    /// The width the input takes next to the matches, a quarter of the
+    /// space the prompt leaves, so a long prompt doesn't hide them.
     #[inline]
-    pub fn insert_char_at_caret(&mut self, letter: char) {
-        if self.buffer.len() < u16::MAX as usize {
-            self.buffer
-                .insert(self.caret_position as usize, letter);
-            self.advance_caret();
-        }
+    pub const fn width_beside_pager(window_width: u32, prompt_width: u32) -> u32 {
+        window_width.saturating_sub(prompt_width) / 4
     }
 
     #[inline]
-    pub fn remove_char_at_caret(&mut self) {
-        if self.buffer.len() > 0 && self.caret_position > 0 {
-            self.buffer
-                .remove(self.caret_position as usize - 1);
-            self.retreat_caret();
-        }
+    pub fn set_color(&mut self, color: Color) {
+        self.text_color = color;
     }
 
     #[inline]
-    pub fn advance_caret(&mut self) {
-        self.caret_position = min(self.caret_position + 1, self.buffer.len() as u16);
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale;
     }
 
     #[inline]
-    pub fn retreat_caret(&mut self) {
-        if self.caret_position > 0 {
-            self.caret_position -= 1;
-        }
+    pub fn set_selection_color(&mut self, color: Color) {
+        self.selection_color = color;
     }
 
     #[inline]
-    pub fn is_caret_at_end(&self) -> bool {
-        self.caret_position as usize == self.buffer.len()
+    pub fn set_mask(&mut self, mask: Option<char>) {
+        self.mask = mask;
     }
 
-    pub fn keycode_interaction(&mut self, keycode: Keycode) {
-        match keycode {
-            Keycode::Left => self.retreat_caret(),
-            Keycode::Right => self.advance_caret(),
+    // The characters shown for the input, secret input is masked.
+    fn shown_chars<'s>(&self, state: &'s MenuState) -> Cow<'s, [char]> {
+        let editor = state.editor();
 
-            _ => {},
+        if !state.is_secret() {
+            return Cow::Borrowed(editor.chars());
         }
-    }
 
-    pub fn act_char_at_caret(&mut self, keycode: Keycode, shift: bool) {
-        match keycode {
-            Keycode::Backspace => self.remove_char_at_caret(),
-
-            Keycode::Delete => {
-                self.advance_caret();
-                self.remove_char_at_caret();
-            },
-
-            keycode => {
-                if let Some(char) = keycode_to_char(keycode, shift) {
-                    self.insert_char_at_caret(char);
-                }
-            },
+        match self.mask {
+            Some(mask) => Cow::Owned(vec![mask; editor.chars().len()]),
+            None => Cow::Owned(Vec::new()),
         }
     }
 
     #[inline]
-    pub fn set_color(&mut self, color: Color) {
-        self.text_color = color;
+    fn text_width(&self, chars: &[char]) -> u32 {
+        if chars.is_empty() {
+            return 0;
+        }
+
+        self.font
+            .size_of(
+                &chars
+                    .iter()
+                    .collect::<String>(),
+            )
+            .map(|(w, _)| w)
+            .unwrap_or(0)
     }
 
-    #[inline]
-    pub fn set_position(&mut self, position: Vector2I) {
-        self.position = position;
+    // Finds the character boundary closest to a
+    // horizontal offset relative to the input position.
+    fn caret_for_offset(&self, state: &MenuState, offset: i32) -> usize {
+        let chars = self.shown_chars(state);
+        let mut previous_width = 0;
+
+        for index in 1..=chars.len() {
+            let width = self.text_width(&chars[..index]) as i32;
+
+            if offset < (previous_width + width) / 2 {
+                return index - 1;
+            }
+
+            previous_width = width;
+        }
+
+        chars.len()
     }
+}
 
-    pub fn set_text(&mut self, text: &str) {
-        self.buffer = text
-            .chars()
-            .collect();
-        self.caret_position = self.buffer.len() as u16;
+impl<T: RenderTarget> Component<T> for TextInput<'_> {
+    fn layout(&mut self, bounds: Rect, _: &mut MenuState) -> Result<(), GenericComponentError> {
+        self.bounds = bounds;
+        Ok(())
     }
 
-    pub fn get_args(&self) -> Vec<String> {
-        self.buffer
-            .iter()
-            .collect::<String>()
-            .split(' ')
-            .map(|a| a.to_string())
-            .collect::<Vec<String>>()
+    fn handle_event(
+        &mut self,
+        event: &Event,
+        state: &mut MenuState,
+    ) -> Result<EventResult, GenericComponentError> {
+        let outcome = match *event {
+            Event::MouseButtonDown { mouse_btn: MouseButton::Left, x, y, .. }
+                if self
+                    .bounds
+                    .contains_point(Point::new(x, y)) =>
+            {
+                self.selecting = true;
+
+                state.handle(MenuInput::SetCaret {
+                    position: self.caret_for_offset(state, x - self.bounds.x()),
+                    extend: is_shift_held(),
+                })
+            },
+
+            // The selection follows the mouse even outside the input.
+            Event::MouseMotion { x, .. } if self.selecting => state.handle(MenuInput::SetCaret {
+                position: self.caret_for_offset(state, x - self.bounds.x()),
+                extend: true,
+            }),
+
+            Event::MouseButtonUp { mouse_btn: MouseButton::Left, .. } if self.selecting => {
+                self.selecting = false;
+
+                state
+                    .editor()
+                    .selected_text()
+                    .map_or(MenuOutcome::Continue, MenuOutcome::PrimarySelection)
+            },
+
+            _ => return Ok(EventResult::Ignored),
+        };
+
+        Ok(EventResult::Consumed(outcome))
     }
 
-    pub fn draw(
+    fn draw(
         &self,
-        renderer: &mut Canvas<Window>,
-        texture_creator: &TextureCreator<WindowContext>,
-    ) -> Result<(), TextInputError> {
-        let text = self
-            .buffer
+        state: &MenuState,
+        renderer: &mut Canvas<T>,
+        texture_cache: &mut TextureCache<'_, T::Context>,
+    ) -> Result<(), GenericComponentError> {
+        let editor = state.editor();
+        let chars = self.shown_chars(state);
+        let text = chars
             .iter()
             .collect::<String>();
 
-        let surface = self
-            .font
-            .render(&{ if text.is_empty() { " ".into() } else { text } })
-            .blended(self.text_color)?;
-
-        let texture = texture_creator.create_texture_from_surface(&surface)?;
-
-        renderer
-            .copy(
-                &texture,
-                None,
-                Some(Rect::new(
-                    self.position.x(),
-                    self.position.y(),
-                    surface.width(),
-                    surface.height(),
-                )),
-            )
-            .map_err(|e| TextInputError::Sdl(e))?;
-
-        let carret_offset_x = if self.caret_position == 0 {
-            0
-        } else {
-            let caret_str = self
-                .buffer
-                .iter()
-                .take(self.caret_position as usize)
-                .collect::<String>();
-
-            self.font
-                .size_of(&caret_str)
-                .map(|(w, _)| w)
-                .unwrap_or(0)
-        };
+        if let Some((start, end)) = editor.selection() {
+            let start_x = self.text_width(&chars[..start.min(chars.len())]);
+            let end_x = self.text_width(&chars[..end.min(chars.len())]);
+
+            let curr_draw_color = renderer.draw_color();
+            renderer.set_draw_color(self.selection_color);
+
+            renderer
+                .fill_rect(Rect::new(
+                    self.bounds.x() + start_x as i32,
+                    self.bounds.y(),
+                    end_x - start_x,
+                    self.font
+                        .primary()
+                        .height() as u32,
+                ))
+                .map_err(GenericComponentError::Sdl)?;
+
+            renderer.set_draw_color(curr_draw_color);
+        }
+
+        let text = texture_cache.text::<GenericComponentError>(
+            self.font,
+            if text.is_empty() { " " } else { &text },
+            self.text_color,
+        )?;
+        let text_height = text.height();
+
+        renderer.copy(
+            text.texture(),
+            None,
+            Some(Rect::new(self.bounds.x(), self.bounds.y(), text.width(), text.height())),
+        )?;
+
+        let carret_offset_x = self.text_width(
+            &chars[..editor
+                .caret()
+                .min(chars.len())],
+        );
 
         let curr_draw_color = renderer.draw_color();
         renderer.set_draw_color(self.text_color);
 
-        renderer
-            .fill_rect(Rect::new(
-                self.position.x() as i32 + carret_offset_x as i32,
-                self.position.y() as i32,
-                2,
-                surface.height(),
-            ))
-            .map_err(|e| TextInputError::Sdl(e))?;
+        renderer.fill_rect(Rect::new(
+            self.bounds.x() + carret_offset_x as i32,
+            self.bounds.y(),
+            scale_u32(2, self.scale),
+            text_height,
+        ))?;
 
         renderer.set_draw_color(curr_draw_color);
 
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use sdl2::pixels::Color;
+use sdl2::render::{Texture, TextureCreator, TextureValueError};
+use sdl2::surface::Surface;
+use sdl2::ttf::FontError;
+
+use crate::utils::fonts::FontSet;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct TextKey {
+    text: String,
+    color: u32,
+    font: u64,
+}
+
+pub struct CachedText<'t> {
+    texture: Texture<'t>,
+    width: u32,
+    height: u32,
+
+    last_used: u64,
+}
+
+impl<'t> CachedText<'t> {
+    #[inline]
+    pub fn texture(&self) -> &Texture<'t> {
+        &self.texture
+    }
+
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+/// Keeps the rendered text textures between frames, so text is
+/// only rasterised again when its content, color or font changes.
+///
+/// Textures not used during a frame are dropped by `finish_frame`,
+/// so only what's on screen stays in memory.
+pub struct TextureCache<'t, T> {
+    texture_creator: &'t TextureCreator<T>,
+    entries: HashMap<TextKey, CachedText<'t>>,
+    images: HashMap<PathBuf, CachedText<'t>>,
+
+    frame: u64,
+}
+
+impl<'t, T> TextureCache<'t, T> {
+    pub fn new(texture_creator: &'t TextureCreator<T>) -> Self {
+        Self {
+            texture_creator,
+            entries: HashMap::new(),
+            images: HashMap::new(),
+            frame: 0,
+        }
+    }
+
+    /// Gets the texture for a text, rendering it if it's not cached.
+    pub fn text<E>(
+        &mut self,
+        font: &FontSet,
+        text: &str,
+        color: Color,
+    ) -> Result<&CachedText<'t>, E>
+    where
+        E: From<FontError> + From<TextureValueError>,
+    {
+        let key = TextKey {
+            text: text.to_string(),
+            color: u32::from_be_bytes([color.r, color.g, color.b, color.a]),
+            font: font.id(),
+        };
+
+        if !self
+            .entries
+            .contains_key(&key)
+        {
+            let surface = font.render_blended(text, color)?;
+            let texture = self
+                .texture_creator
+                .create_texture_from_surface(&surface)?;
+
+            self.entries.insert(
+                key.clone(),
+                CachedText {
+                    texture,
+                    width: surface.width(),
+                    height: surface.height(),
+
+                    last_used: self.frame,
+                },
+            );
+        }
+
+        let entry = self
+            .entries
+            .get_mut(&key)
+            .expect("Texture to be cached.");
+        entry.last_used = self.frame;
+
+        Ok(entry)
+    }
+
+    /// Gets the texture for a BMP image, loading it if it's not cached.
+    pub fn image<E>(&mut self, path: &Path) -> Result<&CachedText<'t>, E>
+    where
+        E: From<String> + From<TextureValueError>,
+    {
+        if !self
+            .images
+            .contains_key(path)
+        {
+            let surface = Surface::load_bmp(path)?;
+            let texture = self
+                .texture_creator
+                .create_texture_from_surface(&surface)?;
+
+            self.images.insert(
+                path.to_path_buf(),
+                CachedText {
+                    texture,
+                    width: surface.width(),
+                    height: surface.height(),
+
+                    last_used: self.frame,
+                },
+            );
+        }
+
+        let image = self
+            .images
+            .get_mut(path)
+            .expect("Texture to be cached.");
+        image.last_used = self.frame;
+
+        Ok(image)
+    }
+
+    /// Drops the textures that weren't used since the last call.
+    pub fn finish_frame(&mut self) {
+        let frame = self.frame;
+
+        self.entries
+            .retain(|_, entry| entry.last_used == frame);
+        self.images
+            .retain(|_, image| image.last_used == frame);
+        self.frame += 1;
+    }
+}
@@ -0,0 +1,120 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+use log::{info, warn};
+
+/// A fontconfig pattern like `"JetBrains Mono:size=12"`,
+/// the same syntax dmenu accepts with `-fn`.
+#[derive(Debug, Clone)]
+pub struct FontPattern {
+    pattern: String,
+}
+
+impl FontPattern {
+    #[inline]
+    pub fn new(pattern: &str) -> Self {
+        Self { pattern: pattern.to_string() }
+    }
+
+    #[inline]
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// The requested family, the part before the first `:`.
+    pub fn family(&self) -> &str {
+        self.pattern
+            .split(':')
+            .next()
+            .unwrap_or("")
+            .trim()
+    }
+
+    /// The size requested with `size=` or `pixelsize=`, if any.
+    pub fn size(&self) -> Option<f64> {
+        self.pattern
+            .split(':')
+            .skip(1)
+            .find_map(|property| {
+                let (key, value) = property.split_once('=')?;
+
+                match key.trim() {
+                    "size" | "pixelsize" => value
+                        .trim()
+                        .parse::<f64>()
+                        .ok(),
+                    _ => None,
+                }
+            })
+    }
+
+    /// Resolves the pattern to a font file with `fc-match`.
+    pub fn resolve(&self) -> Option<PathBuf> {
+        let output = Command::new("fc-match")
+            .args(["--format=%{file}\n%{family}", &self.pattern])
+            .output()
+            .ok()?; // if fontconfig is not installed the pattern can't be resolved.
+
+        if !output
+            .status
+            .success()
+        {
+            return None;
+        }
+
+        let output = String::from_utf8_lossy(&output.stdout);
+        let mut lines = output.lines();
+
+        let path = PathBuf::from(lines.next()?);
+        let families = lines
+            .next()
+            .unwrap_or("");
+
+        if !path.is_file() {
+            return None;
+        }
+
+        let family = self
+            .family()
+            .to_lowercase();
+
+        if !family.is_empty()
+            && !families
+                .split(',')
+                .any(|found| found.to_lowercase() == family)
+        {
+            warn!(
+                "Fontconfig has no '{}' family, it was substituted by '{families}'",
+                self.family()
+            );
+        }
+
+        info!("Resolved font pattern '{}' to '{}'", self.pattern, path.display());
+
+        Some(path)
+    }
+
+    /// What fontconfig considers the closest matches for
+    /// this pattern, used to make loading errors actionable.
+    pub fn candidates(&self) -> Vec<String> {
+        const MAX_CANDIDATES: usize = 5;
+
+        Command::new("fc-match")
+            .args(["--sort", "--format=%{family[0]} (%{file})\n", &self.pattern])
+            .output()
+            .ok()
+            .filter(|output| {
+                output
+                    .status
+                    .success()
+            })
+            .map(|output| {
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .take(MAX_CANDIDATES)
+                    .map(|line| line.to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,316 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use sdl2::keyboard::{Keycode, Mod};
+use toml_edit::Item as TomlItem;
+
+use super::types::ConfigValueError;
+
+macro_rules! actions {
+    ($($(#[$meta:meta])* $variant:ident => $name:literal),* $(,)?) => {
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum Action {
+            $($(#[$meta])* $variant),*
+        }
+
+        impl Action {
+            pub const NAMES: &[&'static str] = &[$($name),*];
+        }
+
+        impl FromStr for Action {
+            type Err = ();
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                match value {
+                    $($name => Ok(Self::$variant),)*
+                    _ => Err(()),
+                }
+            }
+        }
+    };
+}
+
+actions! {
+    /// Start the selected entry or what's typed.
+    Accept => "accept",
+    /// Like accept, but start it in a terminal emulator.
+    AcceptInTerminal => "accept-in-terminal",
+    /// Close the menu without doing anything.
+    Cancel => "cancel",
+    /// Replace the input with the selected entry.
+    Complete => "complete",
+
+    /// Mark or unmark the selected entry in multi-select mode.
+    ToggleMark => "toggle-mark",
+
+    /// Select the next entry.
+    Next => "next",
+    /// Select the previous entry.
+    Prev => "prev",
+    /// Select the first entry of the next page.
+    PageNext => "page-next",
+    /// Select the first entry of the previous page.
+    PagePrev => "page-prev",
+
+    /// Move the input caret left, or select the previous
+    /// entry if an entry after the first one is selected.
+    Left => "left",
+    /// Move the input caret right, or select the next
+    /// entry if the caret is already at the end.
+    Right => "right",
+
+    /// Move the input caret to the previous word start.
+    WordLeft => "word-left",
+    /// Move the input caret to the next word end.
+    WordRight => "word-right",
+    /// Move the input caret to the start.
+    LineStart => "line-start",
+    /// Move the input caret to the end.
+    LineEnd => "line-end",
+
+    /// Remove the character before the caret.
+    DeleteBackward => "delete-backward",
+    /// Remove the character after the caret.
+    DeleteForward => "delete-forward",
+    /// Kill the whitespace separated word before the caret.
+    DeleteWord => "delete-word",
+    /// Kill the word after the caret.
+    DeleteWordForward => "delete-word-forward",
+    /// Kill everything before the caret.
+    KillLineStart => "kill-line-start",
+    /// Kill everything after the caret.
+    KillLineEnd => "kill-line-end",
+
+    /// Insert the last killed text.
+    Yank => "yank",
+    /// Replace the text just yanked with an older kill.
+    YankPop => "yank-pop",
+    /// Select the character before the caret.
+    SelectLeft => "select-left",
+    /// Select the character after the caret.
+    SelectRight => "select-right",
+    /// Select up to the previous word start.
+    SelectWordLeft => "select-word-left",
+    /// Select up to the next word end.
+    SelectWordRight => "select-word-right",
+    /// Select up to the input start.
+    SelectLineStart => "select-line-start",
+    /// Select up to the input end.
+    SelectLineEnd => "select-line-end",
+    /// Select the whole input.
+    SelectAll => "select-all",
+
+    /// Copy the selected text to the clipboard.
+    Copy => "copy",
+    /// Insert the clipboard text at the caret.
+    Paste => "paste",
+    /// Insert the primary selection text at the caret.
+    PastePrimary => "paste-primary",
+
+    /// Undo the last edit.
+    Undo => "undo",
+    /// Redo the last undone edit.
+    Redo => "redo",
+
+    /// Switch between matching text and regular expressions.
+    ToggleRegex => "toggle-regex",
+
+    /// Show the next mode, such as the desktop applications.
+    NextMode => "next-mode",
+    /// Show the previous mode.
+    PrevMode => "prev-mode",
+}
+
+/// A key combined with the modifiers that must be held,
+/// written as `ctrl+shift+tab` in the configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    keycode: Keycode,
+
+    ctrl: bool,
+    alt: bool,
+    shift: bool,
+    super_key: bool,
+}
+
+impl KeyChord {
+    #[inline]
+    pub fn new(keycode: Keycode, modifiers: Mod) -> Self {
+        Self {
+            keycode,
+
+            ctrl: modifiers.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD),
+            // The right alt is AltGr in most layouts, it types
+            // characters instead of being part of a binding.
+            alt: modifiers.contains(Mod::LALTMOD),
+            shift: modifiers.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD),
+            super_key: modifiers.intersects(Mod::LGUIMOD | Mod::RGUIMOD),
+        }
+    }
+
+    #[inline]
+    pub const fn has_command_modifier(&self) -> bool {
+        self.ctrl || self.alt || self.super_key
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = Mod::NOMOD;
+        let mut keycode = None;
+
+        for part in value.split('+') {
+            match part
+                .trim()
+                .to_lowercase()
+                .as_str()
+            {
+                "ctrl" | "control" => modifiers |= Mod::LCTRLMOD,
+                "alt" | "meta" => modifiers |= Mod::LALTMOD,
+                "shift" => modifiers |= Mod::LSHIFTMOD,
+                "super" | "mod4" | "gui" => modifiers |= Mod::LGUIMOD,
+
+                name => {
+                    if keycode.is_some() {
+                        return Err("has more than one key".to_string());
+                    }
+
+                    keycode = Some(match name {
+                        "plus" => Keycode::Plus,
+                        name => Keycode::from_name(&name.replace('_', " "))
+                            .ok_or_else(|| format!("has an unknown key '{part}'"))?,
+                    });
+                },
+            }
+        }
+
+        Ok(Self::new(keycode.ok_or_else(|| "has no key".to_string())?, modifiers))
+    }
+}
+
+/// The table mapping chords to actions, the `[keybindings]`
+/// configuration table is merged over the defaults.
+#[derive(Clone, Debug)]
+pub struct Keybindings {
+    bindings: HashMap<KeyChord, Action>,
+}
+
+impl Keybindings {
+    #[inline]
+    pub fn action_for(&self, chord: &KeyChord) -> Option<Action> {
+        self.bindings
+            .get(chord)
+            .copied()
+    }
+
+    fn bind(&mut self, chord: &str, action: Action) {
+        self.bindings
+            .insert(
+                chord
+                    .parse()
+                    .expect("Default keybindings to be valid."),
+                action,
+            );
+    }
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        let mut keybindings = Self { bindings: HashMap::new() };
+
+        for (chords, action) in [
+            (&["return", "keypad_enter", "ctrl+m"][..], Action::Accept),
+            (&["ctrl+return"], Action::AcceptInTerminal),
+            (&["escape", "ctrl+g", "ctrl+c", "ctrl+["], Action::Cancel),
+            (&["tab", "ctrl+i"], Action::Complete),
+            (&["ctrl+space", "shift+return"], Action::ToggleMark),
+            (&["down", "ctrl+n", "ctrl+j"], Action::Next),
+            (&["up", "ctrl+p"], Action::Prev),
+            (&["page_down"], Action::PageNext),
+            (&["page_up"], Action::PagePrev),
+            (&["left"], Action::Left),
+            (&["right"], Action::Right),
+            (&["backspace", "ctrl+h"], Action::DeleteBackward),
+            (&["delete", "ctrl+d"], Action::DeleteForward),
+            (&["ctrl+w", "ctrl+backspace", "alt+backspace"], Action::DeleteWord),
+            (&["alt+d", "ctrl+delete"], Action::DeleteWordForward),
+            (&["ctrl+left", "alt+b"], Action::WordLeft),
+            (&["ctrl+right", "alt+f"], Action::WordRight),
+            (&["home", "ctrl+a"], Action::LineStart),
+            (&["end", "ctrl+e"], Action::LineEnd),
+            (&["ctrl+u"], Action::KillLineStart),
+            (&["ctrl+k"], Action::KillLineEnd),
+            (&["ctrl+y"], Action::Yank),
+            (&["alt+y"], Action::YankPop),
+            (&["ctrl+z", "ctrl+/"], Action::Undo),
+            (&["ctrl+shift+z"], Action::Redo),
+            (&["shift+left"], Action::SelectLeft),
+            (&["shift+right"], Action::SelectRight),
+            (&["ctrl+shift+left"], Action::SelectWordLeft),
+            (&["ctrl+shift+right"], Action::SelectWordRight),
+            (&["shift+home"], Action::SelectLineStart),
+            (&["shift+end"], Action::SelectLineEnd),
+            (&["ctrl+shift+a"], Action::SelectAll),
+            (&["ctrl+shift+c", "ctrl+insert"], Action::Copy),
+            (&["ctrl+v", "ctrl+shift+v"], Action::Paste),
+            (&["shift+insert"], Action::PastePrimary),
+            (&["ctrl+r"], Action::ToggleRegex),
+            (&["ctrl+tab"], Action::NextMode),
+            (&["ctrl+shift+tab"], Action::PrevMode),
+        ] {
+            for chord in chords {
+                keybindings.bind(chord, action);
+            }
+        }
+
+        keybindings
+    }
+}
+
+impl TryFrom<TomlItem> for Keybindings {
+    type Error = ConfigValueError;
+
+    fn try_from(value: TomlItem) -> Result<Self, Self::Error> {
+        let Some(table) = value.as_table_like() else {
+            return Err(ConfigValueError::InvalidType { possible_types: vec!["Table"] });
+        };
+
+        let mut keybindings = Self::default();
+
+        for (chord, action) in table.iter() {
+            let chord = chord
+                .parse::<KeyChord>()
+                .map_err(|message| ConfigValueError::Entry { entry: chord.to_string(), message })?;
+
+            let Some(action) = action.as_str() else {
+                return Err(ConfigValueError::InvalidType { possible_types: vec!["String"] });
+            };
+
+            // "none" removes a default binding.
+            if action == "none" {
+                keybindings
+                    .bindings
+                    .remove(&chord);
+                continue;
+            }
+
+            let action = action
+                .parse::<Action>()
+                .map_err(|_| ConfigValueError::InvalidValue {
+                    possible: Action::NAMES
+                        .iter()
+                        .copied()
+                        .chain(["none"])
+                        .collect(),
+                })?;
+
+            keybindings
+                .bindings
+                .insert(chord, action);
+        }
+
+        Ok(keybindings)
+    }
+}
@@ -2,7 +2,7 @@ use std::env::var;
 use std::fs::{create_dir_all, read_to_string, write as fs_write};
 use std::io::Error as IoError;
 use std::path::Path;
-use std::u16;
+use std::time::Duration;
 
 use log::{info, warn};
 use sdl2::pixels::Color;
@@ -11,14 +11,33 @@ use sdl2::ttf::{Font, InitError as TTFInitError};
 use thiserror::Error;
 use toml_edit::{DocumentMut, Item as TomlItem, TomlError};
 
+use super::fontconfig::FontPattern;
+use super::keybindings::Keybindings;
 use super::types::{
+    CaseSensitivity,
+    ConfigBool,
     ConfigColor,
     ConfigNumber,
     ConfigString,
+    ConfigStringList,
     ConfigValueError,
     ConfigVector2,
+    Matcher,
+    MouseLaunch,
     WindowPosition,
 };
+use crate::completions::entries::{ConfigAliases, ConfigEntries, Entry};
+use crate::launch::options::{
+    IoPriority,
+    LaunchBackend,
+    LaunchDirectory,
+    LaunchEnvironment,
+    LaunchOptions,
+};
+use crate::launch::terminal::TerminalTemplate;
+use crate::modes::script::{ConfigScripts, Script};
+use crate::utils::display_scale::scale_u32;
+use crate::utils::fonts::{FontSet, load_default_font};
 use crate::utils::misc::ttf_context;
 use crate::utils::vector_matrix::Vector2F;
 
@@ -42,8 +61,14 @@ pub enum ConfigError {
     #[error("TTF init error: {0:#}")]
     TTFError(#[from] TTFInitError),
 
-    #[error("Font error ocurred: {message}")]
+    #[error("Font error occurred: {message}")]
     GenericFontError { message: String },
+
+    #[error(
+        "Couldn't load the font '{pattern}', fontconfig found {found}",
+        found = if .found.is_empty() { "nothing".to_string() } else { .found.join(", ") }
+    )]
+    FontLookup { pattern: String, found: Vec<String> },
 }
 
 pub struct Config<'f> {
@@ -74,9 +99,86 @@ pub struct Config<'f> {
     // the original color might opaque.
     highlighted_text_color: Color,
 
-    // The font that will render
-    // all the text in the window.
-    font: Option<Font<'f, 'f>>,
+    // The color of the part of the
+    // entries that matches the input.
+    match_color: Color,
+
+    // The color of the bar under the
+    // entries marked in multi-select mode.
+    mark_color: Color,
+
+    // The text color of the entries
+    // scripts mark as urgent.
+    urgent_color: Color,
+
+    // How letter case is compared when matching.
+    case_sensitivity: CaseSensitivity,
+
+    // How the input filters the entries
+    // until it's toggled with a keybinding.
+    matcher: Matcher,
+
+    // A scale override, if not present
+    // the display scale is detected.
+    scale_override: Option<f32>,
+
+    // The scale every size is multiplied
+    // by, applied with `apply_scale`.
+    scale: f32,
+
+    // The font file, patterns and size, kept
+    // so the fonts can be re-rasterised.
+    font_path: Option<String>,
+    font_patterns: Vec<FontPattern>,
+    font_size: f64,
+
+    // The fonts that will render all the text
+    // in the window, in fallback order.
+    fonts: Option<FontSet<'f>>,
+
+    // The actions bound to each key chord,
+    // merged over the default bindings.
+    keybindings: Keybindings,
+
+    // How many clicks on an entry start it.
+    mouse_launch: MouseLaunch,
+
+    // Whether the launcher closes when
+    // clicking outside of it.
+    dismiss_on_focus_loss: bool,
+
+    // The text before the input and its colors,
+    // the `-p` argument takes precedence.
+    prompt: Option<String>,
+    prompt_text_color: Color,
+    prompt_background_color: Color,
+
+    // How programs are started in a terminal, the
+    // `$TERMINAL` is used if there is no template.
+    terminal: Option<TerminalTemplate>,
+
+    // Programs always started in a terminal, besides
+    // the ones desktop entries mark as such.
+    terminal_programs: Vec<String>,
+
+    // How programs are started, and the directory,
+    // environment, output log and priorities of them.
+    launch_options: LaunchOptions,
+
+    // The entries and aliases listed among the programs,
+    // started with their own commands instead.
+    entries: Vec<Entry>,
+
+    // The scripts shown as menus of their own.
+    scripts: Vec<Script>,
+
+    // The modes switched between when none are given
+    // in the command line, only `run` if empty.
+    modes: Vec<String>,
+
+    // Drawn for each character of a password,
+    // nothing is drawn if there is none.
+    password_mask: Option<char>,
 }
 
 impl<'f> Config<'f> {
@@ -113,19 +215,95 @@ impl<'f> Config<'f> {
             .try_into()
     }
 
+    /// Sets the scale every geometry getter and the font is scaled by,
+    /// the `scale` configuration entry takes precedence over the detected one.
+    pub fn apply_scale(&mut self, detected_scale: f32) -> Result<(), ConfigError> {
+        self.scale = self
+            .scale_override
+            .unwrap_or(detected_scale);
+
+        info!("Applying a scale factor of {}", self.scale);
+
+        self.fonts = FontSet::new(self.load_fonts()?);
+
+        Ok(())
+    }
+
+    #[inline]
+    fn scaled_font_size(&self, font_size: f64) -> u16 {
+        (font_size * self.scale as f64).clamp(0.0, u16::MAX as f64) as u16
+    }
+
+    fn load_font_file(&self, font_path: &Path, font_size: f64) -> Result<Font<'f, 'f>, String> {
+        ttf_context()
+            .map_err(|err| err.to_string())?
+            .load_font_from_rwops(
+                RWops::from_file(font_path, "r")?,
+                self.scaled_font_size(font_size),
+            )
+    }
+
+    // The font path goes first, then the fontconfig patterns
+    // and the embedded font is always the last fallback.
+    fn load_fonts(&self) -> Result<Vec<Font<'f, 'f>>, ConfigError> {
+        let mut fonts = Vec::new();
+
+        if let Some(font_path) = &self.font_path {
+            fonts.push(
+                self.load_font_file(Path::new(font_path), self.font_size)
+                    .map_err(|err| ConfigError::GenericFontError { message: err })?,
+            );
+        }
+
+        for pattern in &self.font_patterns {
+            let font = pattern
+                .resolve()
+                .and_then(|path| {
+                    self.load_font_file(
+                        &path,
+                        pattern
+                            .size()
+                            .unwrap_or(self.font_size),
+                    )
+                    .inspect_err(|err| warn!("Couldn't load '{}': {err}", path.display()))
+                    .ok()
+                })
+                .ok_or_else(|| ConfigError::FontLookup {
+                    pattern: pattern
+                        .pattern()
+                        .to_string(),
+                    found: pattern.candidates(),
+                })?;
+
+            fonts.push(font);
+        }
+
+        fonts.push(
+            load_default_font(self.scaled_font_size(self.font_size))
+                .map_err(|err| ConfigError::GenericFontError { message: err })?,
+        );
+
+        Ok(fonts)
+    }
+
+    #[inline]
+    pub const fn scale(&self) -> f32 {
+        self.scale
+    }
+
     #[inline]
     pub const fn window_position(&self) -> WindowPosition {
         self.window_position
     }
 
     #[inline]
-    pub const fn window_padding(&self) -> Vector2F {
-        self.window_padding
+    pub fn window_padding(&self) -> Vector2F {
+        self.window_padding * self.scale
     }
 
     #[inline]
-    pub const fn window_height(&self) -> u32 {
-        self.window_height
+    pub fn window_height(&self) -> u32 {
+        scale_u32(self.window_height, self.scale)
     }
 
     #[inline]
@@ -138,6 +316,31 @@ impl<'f> Config<'f> {
         self.highlighted_text_color
     }
 
+    #[inline]
+    pub const fn match_color(&self) -> Color {
+        self.match_color
+    }
+
+    #[inline]
+    pub const fn mark_color(&self) -> Color {
+        self.mark_color
+    }
+
+    #[inline]
+    pub const fn urgent_color(&self) -> Color {
+        self.urgent_color
+    }
+
+    #[inline]
+    pub const fn case_sensitivity(&self) -> CaseSensitivity {
+        self.case_sensitivity
+    }
+
+    #[inline]
+    pub const fn matcher(&self) -> Matcher {
+        self.matcher
+    }
+
     #[inline]
     pub const fn text_color(&self) -> Color {
         self.text_color
@@ -149,8 +352,75 @@ impl<'f> Config<'f> {
     }
 
     #[inline]
-    pub const fn font(&self) -> Option<&Font<'f, 'f>> {
-        self.font.as_ref()
+    pub const fn mouse_launch(&self) -> MouseLaunch {
+        self.mouse_launch
+    }
+
+    #[inline]
+    pub const fn dismiss_on_focus_loss(&self) -> bool {
+        self.dismiss_on_focus_loss
+    }
+
+    #[inline]
+    pub fn prompt(&self) -> Option<&str> {
+        self.prompt
+            .as_deref()
+    }
+
+    #[inline]
+    pub const fn prompt_text_color(&self) -> Color {
+        self.prompt_text_color
+    }
+
+    #[inline]
+    pub const fn prompt_background_color(&self) -> Color {
+        self.prompt_background_color
+    }
+
+    #[inline]
+    pub const fn terminal(&self) -> Option<&TerminalTemplate> {
+        self.terminal
+            .as_ref()
+    }
+
+    #[inline]
+    pub fn terminal_programs(&self) -> &[String] {
+        &self.terminal_programs
+    }
+
+    #[inline]
+    pub const fn launch_options(&self) -> &LaunchOptions {
+        &self.launch_options
+    }
+
+    #[inline]
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    #[inline]
+    pub fn scripts(&self) -> &[Script] {
+        &self.scripts
+    }
+
+    #[inline]
+    pub fn modes(&self) -> &[String] {
+        &self.modes
+    }
+
+    #[inline]
+    pub const fn password_mask(&self) -> Option<char> {
+        self.password_mask
+    }
+
+    #[inline]
+    pub const fn keybindings(&self) -> &Keybindings {
+        &self.keybindings
+    }
+
+    #[inline]
+    pub const fn fonts(&self) -> Option<&FontSet<'f>> {
+        self.fonts.as_ref()
     }
 }
 
@@ -202,34 +472,154 @@ impl<'f> TryFrom<DocumentMut> for Config<'f> {
             window_background_color:
                 handle_value!(window_background_color: ConfigColor | ConfigColor::new(41, 41, 41))
                     .into(),
+            match_color: handle_value!(match_color: ConfigColor | ConfigColor::new(240, 198, 116))
+                .into(),
+            mark_color: handle_value!(mark_color: ConfigColor | ConfigColor::new(129, 162, 190))
+                .into(),
+            urgent_color: handle_value!(urgent_color: ConfigColor | ConfigColor::new(204, 102, 102))
+                .into(),
 
-            font: if let Some(font_path) = handle_value!(font_path: ConfigString) {
-                Some({
-                    let ttf_context = ttf_context()?;
-                    let font_size =
-                        *handle_value!(font_size: ConfigNumber | ConfigNumber::new(14.0));
+            case_sensitivity:
+                handle_value!(case_sensitivity: CaseSensitivity | CaseSensitivity::Sensitive),
+            matcher: handle_value!(matcher: Matcher | Matcher::Text),
+
+            scale_override: handle_value!(scale: ConfigNumber)
+                .map(|scale| {
+                    if *scale > 0.0 {
+                        Ok(*scale as f32)
+                    } else {
+                        Err(ConfigError::InvalidValue {
+                            key: "scale",
+                            message: ConfigValueError::InvalidValue {
+                                possible: vec!["a number above 0"],
+                            },
+                        })
+                    }
+                })
+                .transpose()?,
+            scale: 1.0,
+
+            font_path: handle_value!(font_path: ConfigString)
+                .map(|font_path| {
+                    if !Path::new(&*font_path).exists() {
+                        return Err(ConfigError::GenericFontError {
+                            message: "The font file does not exist.".to_string(),
+                        });
+                    }
 
-                    {
-                        let font_path = Path::new(&*font_path);
+                    Ok(font_path.to_string())
+                })
+                .transpose()?,
+            font_patterns: handle_value!(font: ConfigStringList)
+                .map(|patterns| {
+                    patterns
+                        .iter()
+                        .map(|pattern| FontPattern::new(pattern))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            font_size: *handle_value!(font_size: ConfigNumber | ConfigNumber::new(14.0)),
+
+            fonts: None,
 
-                        if !font_path.exists() {
-                            return Err(ConfigError::GenericFontError {
-                                message: "The font file does not exist.".to_string(),
-                            });
+            keybindings: handle_value!(keybindings: Keybindings | Keybindings::default()),
+
+            mouse_launch: handle_value!(mouse_launch: MouseLaunch | MouseLaunch::DoubleClick),
+            dismiss_on_focus_loss: *handle_value!(dismiss_on_focus_loss: ConfigBool | ConfigBool::new(false)),
+
+            prompt: handle_value!(prompt: ConfigString)
+                .map(|prompt| prompt.to_string())
+                .filter(|prompt| !prompt.is_empty()),
+            prompt_text_color:
+                handle_value!(prompt_text_color: ConfigColor | ConfigColor::new(255, 255, 255))
+                    .into(),
+            prompt_background_color:
+                handle_value!(prompt_background_color: ConfigColor | ConfigColor::new(102, 102, 102))
+                    .into(),
+
+            terminal: handle_value!(terminal: TerminalTemplate),
+            terminal_programs: handle_value!(terminal_programs: ConfigStringList)
+                .map(|programs| programs.to_vec())
+                .unwrap_or_default(),
+
+            launch_options: LaunchOptions::new(
+                handle_value!(launch_backend: LaunchBackend | LaunchBackend::Direct),
+                handle_value!(launch_directory: LaunchDirectory | LaunchDirectory::Inherit),
+                handle_value!(launch_env: LaunchEnvironment)
+                    .map(|environment| environment.to_vec())
+                    .unwrap_or_default(),
+                handle_value!(launch_unset_env: ConfigStringList)
+                    .map(|names| names.to_vec())
+                    .unwrap_or_default(),
+                *handle_value!(launch_log: ConfigBool | ConfigBool::new(false)),
+                handle_value!(launch_nice: ConfigNumber)
+                    .map(|nice| {
+                        if nice.fract() == 0.0 && (-20.0..=19.0).contains(&*nice) {
+                            Ok(*nice as i32)
+                        } else {
+                            Err(ConfigError::InvalidValue {
+                                key: "launch_nice",
+                                message: ConfigValueError::InvalidValue {
+                                    possible: vec!["a whole number from -20 to 19"],
+                                },
+                            })
+                        }
+                    })
+                    .transpose()?,
+                handle_value!(launch_ionice: IoPriority),
+                handle_value!(launch_wait: ConfigNumber)
+                    .map(|wait| {
+                        if *wait >= 0.0 {
+                            Ok(Duration::from_millis(*wait as u64))
+                        } else {
+                            Err(ConfigError::InvalidValue {
+                                key: "launch_wait",
+                                message: ConfigValueError::InvalidValue {
+                                    possible: vec!["a number of milliseconds"],
+                                },
+                            })
                         }
+                    })
+                    .transpose()?
+                    .unwrap_or(Duration::from_millis(100)),
+            ),
+
+            entries: {
+                let mut entries = handle_value!(entries: ConfigEntries)
+                    .map(|entries| entries.to_vec())
+                    .unwrap_or_default();
+
+                for alias in handle_value!(aliases: ConfigAliases)
+                    .map(|aliases| aliases.to_vec())
+                    .unwrap_or_default()
+                {
+                    if entries
+                        .iter()
+                        .any(|entry| entry.name() == alias.name())
+                    {
+                        warn!("The alias '{}' is also an entry, ignoring it", alias.name());
+                        continue;
                     }
 
-                    ttf_context
-                        .load_font_from_rwops(
-                            RWops::from_file(&*font_path, "")
-                                .map_err(|err| ConfigError::GenericFontError { message: err })?,
-                            font_size.clamp(0.0, u16::MAX as f64) as u16,
-                        )
-                        .map_err(|err| ConfigError::GenericFontError { message: err })?
-                })
-            } else {
-                None
+                    entries.push(alias);
+                }
+
+                entries
             },
+
+            scripts: handle_value!(scripts: ConfigScripts)
+                .map(|scripts| scripts.to_vec())
+                .unwrap_or_default(),
+
+            modes: handle_value!(modes: ConfigStringList)
+                .map(|modes| modes.to_vec())
+                .unwrap_or_default(),
+
+            password_mask: handle_value!(password_mask: ConfigString)
+                .map_or(Some('•'), |mask| {
+                    mask.chars()
+                        .next()
+                }),
         })
     }
 }
@@ -1,2 +1,4 @@
+pub mod fontconfig;
+pub mod keybindings;
 pub mod loader;
 pub mod types;
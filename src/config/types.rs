@@ -37,6 +37,9 @@ pub enum ConfigValueError {
         possible = join_possible(.possible)
     )]
     InvalidValue { possible: Vec<&'static str> },
+
+    #[error("'{entry}' {message}")]
+    Entry { entry: String, message: String },
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -45,6 +48,28 @@ pub enum WindowPosition {
     Bottom,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MouseLaunch {
+    SingleClick,
+    DoubleClick,
+}
+
+/// How letter case is compared when matching,
+/// `Smart` is only sensitive if the query has uppercase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaseSensitivity {
+    Sensitive,
+    Insensitive,
+    Smart,
+}
+
+/// How the input filters the entries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Matcher {
+    Text,
+    Regex,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct ConfigVector2 {
     x: f64,
@@ -61,9 +86,15 @@ pub struct ConfigColor {
 #[derive(Debug, Clone, Copy)]
 pub struct ConfigNumber(f64);
 
+#[derive(Debug, Clone, Copy)]
+pub struct ConfigBool(bool);
+
 #[derive(Debug, Clone)]
 pub struct ConfigString(String);
 
+#[derive(Debug, Clone)]
+pub struct ConfigStringList(Vec<String>);
+
 impl TryFrom<TomlItem> for WindowPosition {
     type Error = ConfigValueError;
 
@@ -83,6 +114,75 @@ impl TryFrom<TomlItem> for WindowPosition {
     }
 }
 
+impl MouseLaunch {
+    /// How many clicks on an entry start it.
+    #[inline]
+    pub const fn clicks(&self) -> u8 {
+        match self {
+            Self::SingleClick => 1,
+            Self::DoubleClick => 2,
+        }
+    }
+}
+
+impl TryFrom<TomlItem> for MouseLaunch {
+    type Error = ConfigValueError;
+
+    fn try_from(value: TomlItem) -> Result<Self, Self::Error> {
+        if let Some(value) = value.as_str() {
+            match value
+                .to_lowercase()
+                .as_str()
+            {
+                "single" => Ok(Self::SingleClick),
+                "double" => Ok(Self::DoubleClick),
+                _ => Err(ConfigValueError::InvalidValue { possible: vec!["single", "double"] }),
+            }
+        } else {
+            Err(conf_err!(expected types: String))
+        }
+    }
+}
+
+impl TryFrom<TomlItem> for CaseSensitivity {
+    type Error = ConfigValueError;
+
+    fn try_from(value: TomlItem) -> Result<Self, Self::Error> {
+        if let Some(value) = value.as_str() {
+            match value
+                .to_lowercase()
+                .as_str()
+            {
+                "sensitive" => Ok(Self::Sensitive),
+                "insensitive" => Ok(Self::Insensitive),
+                "smart" => Ok(Self::Smart),
+                _ => Err(conf_err!(expected values: "sensitive", "insensitive", "smart")),
+            }
+        } else {
+            Err(conf_err!(expected types: String))
+        }
+    }
+}
+
+impl TryFrom<TomlItem> for Matcher {
+    type Error = ConfigValueError;
+
+    fn try_from(value: TomlItem) -> Result<Self, Self::Error> {
+        if let Some(value) = value.as_str() {
+            match value
+                .to_lowercase()
+                .as_str()
+            {
+                "text" => Ok(Self::Text),
+                "regex" => Ok(Self::Regex),
+                _ => Err(conf_err!(expected values: "text", "regex")),
+            }
+        } else {
+            Err(conf_err!(expected types: String))
+        }
+    }
+}
+
 impl ConfigVector2 {
     #[inline]
     pub(super) const fn new(x: f64, y: f64) -> Self {
@@ -219,6 +319,32 @@ impl Deref for ConfigNumber {
     }
 }
 
+impl ConfigBool {
+    #[inline]
+    pub(crate) const fn new(value: bool) -> Self {
+        Self(value)
+    }
+}
+
+impl TryFrom<TomlItem> for ConfigBool {
+    type Error = ConfigValueError;
+
+    fn try_from(value: TomlItem) -> Result<Self, Self::Error> {
+        value
+            .as_bool()
+            .map(Self)
+            .ok_or(conf_err!(expected types: bool))
+    }
+}
+
+impl Deref for ConfigBool {
+    type Target = bool;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
 impl TryFrom<TomlItem> for ConfigString {
     type Error = ConfigValueError;
 
@@ -237,3 +363,35 @@ impl Deref for ConfigString {
         &self.0
     }
 }
+
+impl TryFrom<TomlItem> for ConfigStringList {
+    type Error = ConfigValueError;
+
+    fn try_from(value: TomlItem) -> Result<Self, Self::Error> {
+        if let Some(string) = value.as_str() {
+            return Ok(Self(vec![string.to_string()]));
+        }
+
+        if let Some(array) = value.as_array() {
+            return array
+                .iter()
+                .map(|item| {
+                    item.as_str()
+                        .map(|s| s.to_string())
+                        .ok_or(conf_err!(expected types: String, Vec<String>))
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(Self);
+        }
+
+        Err(conf_err!(expected types: String, Vec<String>))
+    }
+}
+
+impl Deref for ConfigStringList {
+    type Target = Vec<String>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
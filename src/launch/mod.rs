@@ -0,0 +1,296 @@
+pub mod options;
+pub mod systemd;
+pub mod terminal;
+
+use std::ffi::OsStr;
+use std::io::Error as IoError;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use log::{info, warn};
+use thiserror::Error;
+
+use self::options::{CapturedOutput, LaunchBackend, LaunchOptions};
+use self::systemd::scope_command;
+use self::terminal::desktop_terminal_programs;
+use crate::completions::entries::Entry;
+use crate::utils::shell_words::{ShellWordsError, split_words};
+
+// How often a started program is checked while it's waited for.
+const WAIT_INTERVAL: Duration = Duration::from_millis(10);
+
+// How many of the last lines a program wrote are shown when it fails.
+const OUTPUT_LINES: usize = 3;
+
+#[derive(Error, Debug)]
+pub enum LaunchError {
+    #[error("Couldn't read the command: {0}")]
+    Words(#[from] ShellWordsError),
+
+    #[error("Couldn't start '{program}': {source}")]
+    Spawn { program: String, source: IoError },
+
+    #[error(
+        "'{program}' failed right after starting, {status}{output}",
+        output = .output.as_ref().map(|output| format!(": {output}")).unwrap_or_default()
+    )]
+    Exited {
+        program: String,
+        status: ExitStatus,
+        output: Option<String>,
+    },
+}
+
+/// Builds the command to start, the selected entry is started if
+/// nothing but a program name was typed, otherwise what's typed
+/// is started with its arguments, split like a shell would. Entries
+/// defined in the configuration start their command instead, with
+/// the typed arguments appended.
+pub fn build_command(
+    selected: Option<&str>,
+    input: &str,
+    entries: &[Entry],
+) -> Result<Option<Command>, ShellWordsError> {
+    let mut words = split_words(input)?;
+
+    if words.len() <= 1
+        && let Some(selected) = selected
+    {
+        words = vec![selected.to_string()];
+    }
+
+    let Some(program) = words
+        .first()
+        .filter(|program| !program.is_empty())
+    else {
+        return Ok(None);
+    };
+
+    if let Some(entry) = entries
+        .iter()
+        .find(|entry| entry.name() == program)
+    {
+        info!("'{program}' is an entry, expanding it");
+
+        words.splice(
+            ..1,
+            entry
+                .command()
+                .iter()
+                .cloned(),
+        );
+    }
+
+    info!("Requesting to start '{}'", words.join(" "));
+
+    let mut command = Command::new(&words[0]);
+    command.args(&words[1..]);
+
+    Ok(Some(command))
+}
+
+/// Whether a program runs in a terminal, because it's in the
+/// configured list or a desktop entry says so.
+pub fn is_terminal_program(program: &OsStr, configured: &[String]) -> bool {
+    let Some(program) = program.to_str() else { return false };
+
+    configured
+        .iter()
+        .any(|configured| configured == program)
+        || desktop_terminal_programs().contains(program)
+}
+
+/// Starts a command detached from r-menu, so it keeps running once the
+/// launcher is closed, as the launch options say, along with where its
+/// output is captured if it's waited for.
+pub fn spawn_detached(
+    command: &mut Command,
+    options: &LaunchOptions,
+) -> Result<(Child, Option<CapturedOutput>), IoError> {
+    if options.backend() == LaunchBackend::Systemd {
+        *command = scope_command(command);
+
+        info!("Starting it in a systemd scope");
+    }
+
+    let captured = options.apply(command)?;
+
+    #[cfg(unix)]
+    unsafe {
+        use std::os::unix::process::CommandExt;
+
+        use self::options::set_priorities;
+
+        let nice = options.nice();
+        let io_priority = options.io_priority();
+
+        command.pre_exec(move || {
+            sdl2::libc::setsid();
+            set_priorities(nice, io_priority)
+        });
+    }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+
+        command.creation_flags(0x00000008);
+    }
+
+    let child = command.spawn()?;
+
+    // Closes r-menu's end of the captured output, so
+    // its reader stops once the program is done with it.
+    if captured.is_some() {
+        command.stderr(Stdio::null());
+    }
+
+    Ok((child, captured))
+}
+
+/// Starts a command with `spawn_detached` and waits for it as long as
+/// the launch options say, failing if it exits unsuccessfully meanwhile,
+/// which catches what fails once started, like missing libraries.
+pub fn launch(command: &mut Command, options: &LaunchOptions) -> Result<(), LaunchError> {
+    let program = command
+        .get_program()
+        .to_string_lossy()
+        .into_owned();
+
+    let (mut child, captured) = spawn_detached(command, options)
+        .map_err(|source| LaunchError::Spawn { program: program.clone(), source })?;
+
+    let deadline = Instant::now() + options.wait();
+
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(()),
+
+            Ok(Some(status)) => {
+                return Err(LaunchError::Exited {
+                    program,
+                    status,
+                    output: captured.and_then(|captured| captured.last_lines(OUTPUT_LINES)),
+                });
+            },
+
+            Ok(None) => {},
+
+            Err(err) => {
+                warn!("Couldn't wait for '{program}': {err}");
+                return Ok(());
+            },
+        }
+
+        let now = Instant::now();
+
+        if now >= deadline {
+            return Ok(());
+        }
+
+        sleep(WAIT_INTERVAL.min(deadline - now));
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::launch::options::LaunchDirectory;
+
+    fn waiting(wait: Duration) -> LaunchOptions {
+        LaunchOptions::new(
+            LaunchBackend::Direct,
+            LaunchDirectory::Inherit,
+            Vec::new(),
+            Vec::new(),
+            false,
+            None,
+            None,
+            wait,
+        )
+    }
+
+    fn words(command: &Command) -> Vec<&OsStr> {
+        std::iter::once(command.get_program())
+            .chain(command.get_args())
+            .collect()
+    }
+
+    #[test]
+    fn the_selection_is_started_unless_arguments_are_typed() {
+        let command = build_command(Some("firefox"), "fire", &[])
+            .unwrap()
+            .unwrap();
+        assert_eq!(words(&command), ["firefox"]);
+
+        let command = build_command(Some("firefox"), r#"notify-send "a b" c"#, &[])
+            .unwrap()
+            .unwrap();
+        assert_eq!(words(&command), ["notify-send", "a b", "c"]);
+
+        assert!(
+            build_command(None, "  ", &[])
+                .unwrap()
+                .is_none()
+        );
+        assert!(build_command(None, "echo 'open", &[]).is_err());
+    }
+
+    #[test]
+    fn entries_start_their_command_with_the_typed_arguments() {
+        let entries = [Entry::new("web", "firefox --new-window").unwrap()];
+
+        let command = build_command(Some("web"), "we", &entries)
+            .unwrap()
+            .unwrap();
+        assert_eq!(words(&command), ["firefox", "--new-window"]);
+
+        let command = build_command(None, "web example.org", &entries)
+            .unwrap()
+            .unwrap();
+        assert_eq!(words(&command), ["firefox", "--new-window", "example.org"]);
+    }
+
+    #[test]
+    fn programs_failing_while_waited_for_report_their_output() {
+        let mut command = Command::new("sh");
+        command.args(["-c", "echo 'libfoo.so: cannot open shared object file' >&2; exit 127"]);
+
+        let Err(LaunchError::Exited { status, output, .. }) =
+            launch(&mut command, &waiting(Duration::from_secs(5)))
+        else {
+            panic!("the program to fail");
+        };
+
+        assert_eq!(status.code(), Some(127));
+        assert_eq!(output.as_deref(), Some("libfoo.so: cannot open shared object file"));
+    }
+
+    #[test]
+    fn only_the_last_lines_of_the_output_are_reported() {
+        let mut command = Command::new("sh");
+        command.args(["-c", "for line in 1 2 3 4 5; do echo \"line $line\" >&2; done; exit 1"]);
+
+        let Err(LaunchError::Exited { output, .. }) =
+            launch(&mut command, &waiting(Duration::from_secs(5)))
+        else {
+            panic!("the program to fail");
+        };
+
+        assert_eq!(output.as_deref(), Some("line 3; line 4; line 5"));
+    }
+
+    #[test]
+    fn programs_running_after_the_wait_are_started() {
+        assert!(
+            launch(Command::new("sleep").arg("1"), &waiting(Duration::from_millis(20))).is_ok()
+        );
+
+        let Err(LaunchError::Spawn { .. }) =
+            launch(&mut Command::new("rmenu-missing-program"), &waiting(Duration::ZERO))
+        else {
+            panic!("the program not to be found");
+        };
+    }
+}
@@ -0,0 +1,617 @@
+use std::env::{current_dir, var_os};
+use std::fs::{File, create_dir_all, read_dir, read_to_string, remove_file, write as fs_write};
+use std::io::{Error as IoError, PipeReader, Read, Seek, SeekFrom, Write, pipe};
+use std::iter::once;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread::{JoinHandle, sleep, spawn};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use log::{info, warn};
+use toml_edit::Item as TomlItem;
+
+use crate::config::types::ConfigValueError;
+
+// How many launch logs are kept, older ones are removed.
+const KEPT_LOGS: usize = 50;
+
+// How much of the end of the captured output is read back.
+const OUTPUT_TAIL: u64 = 4096;
+
+// How long the captured output is read after the program exits,
+// its own children may keep writing to it.
+const OUTPUT_GRACE: Duration = Duration::from_millis(100);
+
+/// How programs are started.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LaunchBackend {
+    /// In a new session of their own.
+    #[default]
+    Direct,
+    /// In a transient systemd user scope, so they get their own cgroup.
+    Systemd,
+}
+
+/// Where programs are started.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum LaunchDirectory {
+    /// The directory r-menu was started in.
+    #[default]
+    Inherit,
+    /// The home directory.
+    Home,
+    /// The directory r-menu was started in, which is remembered
+    /// and used instead when started from the home or root
+    /// directory, as window managers usually do.
+    Last,
+    /// A directory, `~` being the home directory.
+    Path(PathBuf),
+}
+
+/// An IO scheduling class and its priority, from 0, the highest, to 7.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IoPriority {
+    Realtime(u8),
+    BestEffort(u8),
+    Idle,
+}
+
+/// Variables and their values, set for started programs.
+#[derive(Clone, Debug, Default)]
+pub struct LaunchEnvironment(Vec<(String, String)>);
+
+/// The directory, environment, output and priorities of started programs.
+#[derive(Clone, Debug, Default)]
+pub struct LaunchOptions {
+    backend: LaunchBackend,
+    directory: LaunchDirectory,
+    environment: Vec<(String, String)>,
+    removed_environment: Vec<String>,
+    log_output: bool,
+    nice: Option<i32>,
+    io_priority: Option<IoPriority>,
+    wait: Duration,
+}
+
+/// The output a started program writes while it's waited for,
+/// read back from its log or a pipe to explain a failure.
+pub enum CapturedOutput {
+    Log {
+        file: File,
+        // Where the output of the program starts.
+        start: u64,
+    },
+    Pipe {
+        // The end of the output, all that's kept of it.
+        tail: Arc<Mutex<Vec<u8>>>,
+        reader: JoinHandle<()>,
+    },
+}
+
+#[inline]
+fn home_directory() -> Option<PathBuf> {
+    var_os("HOME")
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+// The r-menu directory in `$XDG_STATE_HOME`.
+fn state_directory() -> Option<PathBuf> {
+    var_os("XDG_STATE_HOME")
+        .filter(|state_home| !state_home.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home_directory().map(|home| home.join(".local/state")))
+        .map(|state_home| state_home.join("rmenu"))
+}
+
+// The directory for `Last`, the current one unless it's the home
+// or root directory, where the remembered one is used instead.
+fn last_directory(current: &Path, home: Option<&Path>, remembered: Option<PathBuf>) -> PathBuf {
+    if current != Path::new("/") && Some(current) != home {
+        return current.to_path_buf();
+    }
+
+    remembered
+        .filter(|remembered| remembered.is_dir())
+        .unwrap_or_else(|| current.to_path_buf())
+}
+
+impl TryFrom<TomlItem> for LaunchBackend {
+    type Error = ConfigValueError;
+
+    fn try_from(value: TomlItem) -> Result<Self, Self::Error> {
+        let Some(backend) = value.as_str() else {
+            return Err(ConfigValueError::InvalidType { possible_types: vec!["String"] });
+        };
+
+        match backend {
+            "direct" => Ok(Self::Direct),
+            "systemd" => Ok(Self::Systemd),
+            _ => Err(ConfigValueError::InvalidValue { possible: vec!["direct", "systemd"] }),
+        }
+    }
+}
+
+impl LaunchDirectory {
+    /// The directory to start programs in, `None` to inherit it.
+    pub fn resolve(&self) -> Option<PathBuf> {
+        match self {
+            Self::Inherit => None,
+            Self::Home => home_directory(),
+
+            Self::Path(path) => match path.strip_prefix("~") {
+                Ok(relative) => home_directory().map(|home| home.join(relative)),
+                Err(_) => Some(path.clone()),
+            },
+
+            Self::Last => {
+                let current = current_dir().ok()?;
+                let remembered_path = state_directory()?.join("last_directory");
+
+                let remembered = read_to_string(&remembered_path)
+                    .ok()
+                    .map(|remembered| PathBuf::from(remembered.trim_end_matches('\n')));
+
+                let directory = last_directory(&current, home_directory().as_deref(), remembered);
+
+                if let Err(err) = create_dir_all(state_directory()?).and_then(|_| {
+                    fs_write(
+                        &remembered_path,
+                        directory
+                            .as_os_str()
+                            .as_encoded_bytes(),
+                    )
+                }) {
+                    warn!("Couldn't remember the launch directory: {err}");
+                }
+
+                Some(directory)
+            },
+        }
+    }
+}
+
+impl TryFrom<TomlItem> for LaunchDirectory {
+    type Error = ConfigValueError;
+
+    fn try_from(value: TomlItem) -> Result<Self, Self::Error> {
+        let Some(directory) = value.as_str() else {
+            return Err(ConfigValueError::InvalidType { possible_types: vec!["String"] });
+        };
+
+        Ok(match directory {
+            "inherit" => Self::Inherit,
+            "home" => Self::Home,
+            "last" => Self::Last,
+
+            path if path.starts_with('/') || path.starts_with('~') => Self::Path(path.into()),
+
+            _ => {
+                return Err(ConfigValueError::InvalidValue {
+                    possible: vec!["inherit", "home", "last", "an absolute path"],
+                });
+            },
+        })
+    }
+}
+
+impl IoPriority {
+    // The value `ioprio_set` takes, the class shifted over the level.
+    #[cfg(target_os = "linux")]
+    const fn value(&self) -> i32 {
+        match self {
+            Self::Realtime(level) => 1 << 13 | *level as i32,
+            Self::BestEffort(level) => 2 << 13 | *level as i32,
+            Self::Idle => 3 << 13,
+        }
+    }
+}
+
+impl TryFrom<TomlItem> for IoPriority {
+    type Error = ConfigValueError;
+
+    fn try_from(value: TomlItem) -> Result<Self, Self::Error> {
+        let Some(priority) = value.as_str() else {
+            return Err(ConfigValueError::InvalidType { possible_types: vec!["String"] });
+        };
+
+        let invalid = || ConfigValueError::InvalidValue {
+            possible: vec!["idle", "best-effort[:0-7]", "realtime[:0-7]"],
+        };
+
+        let (class, level) = match priority.split_once(':') {
+            Some((class, level)) => (
+                class,
+                level
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|level| *level <= 7)
+                    .ok_or_else(invalid)?,
+            ),
+            // The kernel's default level.
+            None => (priority, 4),
+        };
+
+        match class {
+            "realtime" => Ok(Self::Realtime(level)),
+            "best-effort" => Ok(Self::BestEffort(level)),
+            "idle" if priority == "idle" => Ok(Self::Idle),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl LaunchOptions {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        backend: LaunchBackend,
+        directory: LaunchDirectory,
+        environment: Vec<(String, String)>,
+        removed_environment: Vec<String>,
+        log_output: bool,
+        nice: Option<i32>,
+        io_priority: Option<IoPriority>,
+        wait: Duration,
+    ) -> Self {
+        Self {
+            backend,
+            directory,
+            environment,
+            removed_environment,
+            log_output,
+            nice,
+            io_priority,
+            wait,
+        }
+    }
+
+    #[inline]
+    pub const fn backend(&self) -> LaunchBackend {
+        self.backend
+    }
+
+    #[inline]
+    pub const fn directory(&self) -> &LaunchDirectory {
+        &self.directory
+    }
+
+    /// The variables set for started programs.
+    #[inline]
+    pub fn environment(&self) -> &[(String, String)] {
+        &self.environment
+    }
+
+    /// The variables removed for started programs.
+    #[inline]
+    pub fn removed_environment(&self) -> &[String] {
+        &self.removed_environment
+    }
+
+    /// Whether the output of started programs is
+    /// written to a log file instead of discarded.
+    #[inline]
+    pub const fn log_output(&self) -> bool {
+        self.log_output
+    }
+
+    #[inline]
+    pub const fn nice(&self) -> Option<i32> {
+        self.nice
+    }
+
+    #[inline]
+    pub const fn io_priority(&self) -> Option<IoPriority> {
+        self.io_priority
+    }
+
+    /// How long a started program is waited for,
+    /// in case it fails right after starting.
+    #[inline]
+    pub const fn wait(&self) -> Duration {
+        self.wait
+    }
+
+    /// Sets the directory, environment and output of a command,
+    /// returning where its output is captured if it's waited for.
+    pub fn apply(&self, command: &mut Command) -> Result<Option<CapturedOutput>, IoError> {
+        if let Some(directory) = self
+            .directory
+            .resolve()
+        {
+            info!("Starting it in '{}'", directory.display());
+            command.current_dir(directory);
+        }
+
+        for name in &self.removed_environment {
+            command.env_remove(name);
+        }
+
+        command.envs(
+            self.environment
+                .iter()
+                .map(|(name, value)| (name, value)),
+        );
+
+        command.stdin(Stdio::null());
+
+        if self.log_output {
+            let (log_path, mut log) = create_log(command)?;
+            let start = log.stream_position()?;
+
+            info!("Writing its output to '{}'", log_path.display());
+
+            command.stdout(log.try_clone()?);
+            command.stderr(log);
+
+            if self.wait.is_zero() {
+                return Ok(None);
+            }
+
+            return Ok(Some(CapturedOutput::Log { file: File::open(log_path)?, start }));
+        }
+
+        command.stdout(Stdio::null());
+
+        if self.wait.is_zero() {
+            command.stderr(Stdio::null());
+            return Ok(None);
+        }
+
+        // Only the end of the output is kept, so a program
+        // writing a lot doesn't fill the memory while it runs.
+        let (reader, writer) = pipe()?;
+        command.stderr(writer);
+
+        Ok(Some(CapturedOutput::read_from(reader)))
+    }
+}
+
+impl CapturedOutput {
+    fn read_from(mut pipe: PipeReader) -> Self {
+        let tail = Arc::new(Mutex::new(Vec::new()));
+        let reader_tail = tail.clone();
+
+        let reader = spawn(move || {
+            let mut chunk = [0; 1024];
+
+            while let Ok(read @ 1..) = pipe.read(&mut chunk) {
+                let Ok(mut tail) = reader_tail.lock() else { return };
+
+                tail.extend_from_slice(&chunk[..read]);
+
+                let excess = tail
+                    .len()
+                    .saturating_sub(OUTPUT_TAIL as usize);
+                tail.drain(..excess);
+            }
+        });
+
+        Self::Pipe { tail, reader }
+    }
+
+    // The end of the output, waiting a bit for the
+    // rest of what's written to a pipe to be read.
+    fn tail(self) -> Option<Vec<u8>> {
+        match self {
+            Self::Log { mut file, start } => {
+                let end = file
+                    .seek(SeekFrom::End(0))
+                    .ok()?;
+
+                file.seek(SeekFrom::Start(
+                    end.saturating_sub(OUTPUT_TAIL)
+                        .max(start),
+                ))
+                .ok()?;
+
+                let mut tail = Vec::new();
+                file.read_to_end(&mut tail)
+                    .ok()?;
+
+                Some(tail)
+            },
+
+            Self::Pipe { tail, reader } => {
+                let start = Instant::now();
+
+                while !reader.is_finished() && start.elapsed() < OUTPUT_GRACE {
+                    sleep(Duration::from_millis(1));
+                }
+
+                tail.lock()
+                    .ok()
+                    .map(|tail| tail.clone())
+            },
+        }
+    }
+
+    /// The last lines written, up to `count`, joined
+    /// by semicolons as errors are shown in one line.
+    pub fn last_lines(self, count: usize) -> Option<String> {
+        let tail = self.tail()?;
+
+        let lines = String::from_utf8_lossy(&tail)
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+
+        if lines.is_empty() {
+            return None;
+        }
+
+        Some(
+            lines[lines
+                .len()
+                .saturating_sub(count)..]
+                .join("; "),
+        )
+    }
+}
+
+// Creates the log of a launch in the state directory, named
+// after the time and the program, starting with the command line.
+fn create_log(command: &Command) -> Result<(PathBuf, File), IoError> {
+    let logs_directory = state_directory()
+        .ok_or_else(|| IoError::other("Neither XDG_STATE_HOME nor HOME are set"))?
+        .join("logs");
+
+    create_dir_all(&logs_directory)?;
+    remove_old_logs(&logs_directory);
+
+    let program = Path::new(command.get_program())
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+
+    let log_path = logs_directory.join(format!("{:013}-{program}.log", time.as_millis()));
+    let mut log = File::create(&log_path)?;
+
+    let command_line = once(command.get_program())
+        .chain(command.get_args())
+        .map(|word| word.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    writeln!(log, "$ {command_line}")?;
+
+    Ok((log_path, log))
+}
+
+// Keeps the newest logs, their names start with the time.
+fn remove_old_logs(logs_directory: &Path) {
+    let Ok(entries) = read_dir(logs_directory) else { return };
+
+    let mut logs = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "log")
+        })
+        .collect::<Vec<_>>();
+
+    if logs.len() < KEPT_LOGS {
+        return;
+    }
+
+    logs.sort();
+
+    for log in &logs[..=logs.len() - KEPT_LOGS] {
+        if let Err(err) = remove_file(log) {
+            warn!("Couldn't remove the old log '{}': {err}", log.display());
+        }
+    }
+}
+
+impl TryFrom<TomlItem> for LaunchEnvironment {
+    type Error = ConfigValueError;
+
+    fn try_from(value: TomlItem) -> Result<Self, Self::Error> {
+        let Some(table) = value.as_table_like() else {
+            return Err(ConfigValueError::InvalidType { possible_types: vec!["Table"] });
+        };
+
+        table
+            .iter()
+            .map(|(name, value)| {
+                value
+                    .as_str()
+                    .map(|value| (name.to_string(), value.to_string()))
+                    .ok_or_else(|| ConfigValueError::Entry {
+                        entry: name.to_string(),
+                        message: "should be a String".to_string(),
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self)
+    }
+}
+
+impl Deref for LaunchEnvironment {
+    type Target = [(String, String)];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Sets the niceness and IO priority of the started process.
+#[cfg(unix)]
+pub(super) fn set_priorities(
+    nice: Option<i32>,
+    io_priority: Option<IoPriority>,
+) -> Result<(), IoError> {
+    use sdl2::libc::{PRIO_PROCESS, setpriority};
+
+    if let Some(nice) = nice
+        && unsafe { setpriority(PRIO_PROCESS, 0, nice) } == -1
+    {
+        return Err(IoError::last_os_error());
+    }
+
+    #[cfg(target_os = "linux")]
+    if let Some(io_priority) = io_priority {
+        use sdl2::libc::{SYS_ioprio_set, syscall};
+
+        // IOPRIO_WHO_PROCESS, 0 is the calling process.
+        if unsafe { syscall(SYS_ioprio_set, 1, 0, io_priority.value()) } == -1 {
+            return Err(IoError::last_os_error());
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    let _ = io_priority;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use toml_edit::value;
+
+    use super::*;
+
+    #[test]
+    fn io_priorities_take_an_optional_level() {
+        assert_eq!(IoPriority::try_from(value("idle")).unwrap(), IoPriority::Idle);
+        assert_eq!(IoPriority::try_from(value("best-effort")).unwrap(), IoPriority::BestEffort(4));
+        assert_eq!(IoPriority::try_from(value("realtime:0")).unwrap(), IoPriority::Realtime(0));
+
+        assert!(IoPriority::try_from(value("best-effort:8")).is_err());
+        assert!(IoPriority::try_from(value("idle:1")).is_err());
+    }
+
+    #[test]
+    fn the_last_directory_replaces_home_and_root() {
+        let home = Path::new("/home/me");
+        let remembered = || Some(PathBuf::from("/"));
+
+        assert_eq!(last_directory(Path::new("/tmp"), Some(home), remembered()), Path::new("/tmp"));
+        assert_eq!(last_directory(home, Some(home), remembered()), Path::new("/"));
+        assert_eq!(last_directory(Path::new("/"), Some(home), None), Path::new("/"));
+    }
+
+    #[test]
+    fn environment_values_are_strings() {
+        let table = "launch_env = { GDK_BACKEND = 'wayland', MOZ_ENABLE_WAYLAND = '1' }"
+            .parse::<toml_edit::DocumentMut>()
+            .unwrap();
+
+        assert_eq!(
+            *LaunchEnvironment::try_from(table["launch_env"].clone()).unwrap(),
+            [
+                ("GDK_BACKEND".to_string(), "wayland".to_string()),
+                ("MOZ_ENABLE_WAYLAND".to_string(), "1".to_string())
+            ]
+        );
+        assert!(LaunchEnvironment::try_from(value(1)).is_err());
+    }
+}
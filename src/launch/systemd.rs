@@ -0,0 +1,90 @@
+use std::ffi::OsStr;
+use std::fmt::Write;
+use std::path::Path;
+use std::process::{Command, id as process_id};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Wraps a command so it runs in a transient systemd user scope, as
+/// desktop environments start applications, keeping its working
+/// directory and environment, `systemd-run` then becomes the program.
+pub fn scope_command(command: &Command) -> Command {
+    let mut scoped = Command::new("systemd-run");
+    scoped
+        .args(["--user", "--scope", "--collect", "--quiet"])
+        .arg(format!("--unit={}", unit_name(command.get_program())))
+        .arg("--")
+        .arg(command.get_program())
+        .args(command.get_args());
+
+    if let Some(directory) = command.get_current_dir() {
+        scoped.current_dir(directory);
+    }
+
+    for (key, value) in command.get_envs() {
+        match value {
+            Some(value) => scoped.env(key, value),
+            None => scoped.env_remove(key),
+        };
+    }
+
+    scoped
+}
+
+// The scope name for a program, following the `app-<launcher>-<id>-<random>`
+// convention of the desktop application units, which is unique per launch.
+fn unit_name(program: &OsStr) -> String {
+    let program = Path::new(program)
+        .file_name()
+        .unwrap_or(program)
+        .to_string_lossy();
+
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+
+    format!(
+        "app-rmenu-{}-{:x}{:x}.scope",
+        escape_unit_part(&program),
+        process_id(),
+        time.subsec_nanos()
+    )
+}
+
+// Escapes a part of a unit name as `systemd-escape` does, `-` separates
+// the parts so it's escaped too, as is anything but ASCII alphanumerics,
+// `_`, `:` and a `.` that doesn't start it.
+fn escape_unit_part(part: &str) -> String {
+    let mut escaped = String::with_capacity(part.len());
+
+    for (index, byte) in part
+        .bytes()
+        .enumerate()
+    {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'_' | b':' => escaped.push(byte as char),
+            b'.' if index > 0 => escaped.push('.'),
+            byte => {
+                let _ = write!(escaped, "\\x{byte:02x}");
+            },
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unit_names_escape_the_program() {
+        assert_eq!(escape_unit_part("firefox"), "firefox");
+        assert_eq!(escape_unit_part("gnome-terminal"), "gnome\\x2dterminal");
+        assert_eq!(escape_unit_part(".hidden app"), "\\x2ehidden\\x20app");
+
+        let name = unit_name(OsStr::new("/usr/bin/org.gnome.Nautilus"));
+
+        assert!(name.starts_with("app-rmenu-org.gnome.Nautilus-"));
+        assert!(name.ends_with(".scope"));
+    }
+}
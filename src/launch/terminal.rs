@@ -0,0 +1,271 @@
+use std::collections::HashSet;
+use std::env::{split_paths, var, var_os};
+use std::fs::{read_dir, read_to_string};
+use std::iter::once;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use log::info;
+use toml_edit::Item as TomlItem;
+
+use crate::config::types::ConfigValueError;
+use crate::utils::shell_words::{ShellWordsError, quote_word, split_words};
+
+const COMMAND_PLACEHOLDER: &str = "{cmd}";
+
+/// How programs are started in a terminal emulator, a command line such
+/// as `alacritty -e {cmd}` where `{cmd}` is replaced by the program and its
+/// arguments, which are appended instead if there is no `{cmd}`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TerminalTemplate {
+    words: Vec<String>,
+}
+
+impl TerminalTemplate {
+    #[inline]
+    pub fn parse(template: &str) -> Result<Self, ShellWordsError> {
+        Ok(Self { words: split_words(template)? })
+    }
+
+    /// The terminal in `$TERMINAL`, or `x-terminal-emulator`
+    /// otherwise, both are expected to take `-e`.
+    pub fn fallback() -> Self {
+        let terminal = var("TERMINAL")
+            .ok()
+            .filter(|terminal| !terminal.is_empty())
+            .unwrap_or_else(|| "x-terminal-emulator".to_string());
+
+        let mut words = split_words(&terminal).unwrap_or_else(|_| vec![terminal]);
+        words.extend(["-e".to_string(), COMMAND_PLACEHOLDER.to_string()]);
+
+        Self { words }
+    }
+
+    /// The command starting another one in the terminal,
+    /// keeping its working directory and environment.
+    pub fn wrap(&self, command: &Command) -> Command {
+        let command_words = once(command.get_program())
+            .chain(command.get_args())
+            .map(|word| {
+                word.to_string_lossy()
+                    .into_owned()
+            })
+            .collect::<Vec<_>>();
+
+        let mut words = Vec::with_capacity(self.words.len() + command_words.len());
+        let mut placed = false;
+
+        for word in &self.words {
+            if word == COMMAND_PLACEHOLDER {
+                words.extend(
+                    command_words
+                        .iter()
+                        .cloned(),
+                );
+                placed = true;
+            } else if word.contains(COMMAND_PLACEHOLDER) {
+                // Inside a word, like `sh -c "{cmd}; read"`, the command is quoted.
+                let command_line = command_words
+                    .iter()
+                    .map(|word| quote_word(word))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+
+                words.push(word.replace(COMMAND_PLACEHOLDER, &command_line));
+                placed = true;
+            } else {
+                words.push(word.clone());
+            }
+        }
+
+        if !placed {
+            words.extend(command_words);
+        }
+
+        let mut words = words.into_iter();
+        let mut wrapped = Command::new(
+            words
+                .next()
+                .expect("The command to have a program."),
+        );
+        wrapped.args(words);
+
+        if let Some(directory) = command.get_current_dir() {
+            wrapped.current_dir(directory);
+        }
+
+        for (key, value) in command.get_envs() {
+            match value {
+                Some(value) => wrapped.env(key, value),
+                None => wrapped.env_remove(key),
+            };
+        }
+
+        wrapped
+    }
+}
+
+impl TryFrom<TomlItem> for TerminalTemplate {
+    type Error = ConfigValueError;
+
+    fn try_from(value: TomlItem) -> Result<Self, Self::Error> {
+        let Some(template) = value.as_str() else {
+            return Err(ConfigValueError::InvalidType { possible_types: vec!["String"] });
+        };
+
+        let entry_err =
+            |message: String| ConfigValueError::Entry { entry: template.to_string(), message };
+
+        let template = Self::parse(template).map_err(|err| entry_err(err.to_string()))?;
+
+        if template
+            .words
+            .is_empty()
+        {
+            return Err(entry_err("has no terminal program".to_string()));
+        }
+
+        Ok(template)
+    }
+}
+
+// The `applications` directories of the XDG data directories.
+pub(crate) fn applications_directories() -> Vec<PathBuf> {
+    let data_home = var_os("XDG_DATA_HOME")
+        .filter(|data_home| !data_home.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| var_os("HOME").map(|home| Path::new(&home).join(".local/share")));
+
+    let data_dirs = var_os("XDG_DATA_DIRS")
+        .filter(|data_dirs| !data_dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".into());
+
+    data_home
+        .into_iter()
+        .chain(split_paths(&data_dirs))
+        .map(|directory| directory.join("applications"))
+        .collect()
+}
+
+/// The programs started by the desktop entries that set `Terminal=true`.
+pub fn desktop_terminal_programs() -> HashSet<String> {
+    let mut programs = HashSet::new();
+
+    for directory in applications_directories() {
+        let Ok(entries) = read_dir(&directory) else { continue };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "desktop")
+                && let Ok(desktop_entry) = read_to_string(&path)
+                && let Some(program) = terminal_program(&desktop_entry)
+            {
+                programs.insert(program);
+            }
+        }
+    }
+
+    info!("Found {} terminal programs in desktop entries", programs.len());
+
+    programs
+}
+
+// The name of the program a desktop entry starts, if it's a terminal one.
+fn terminal_program(desktop_entry: &str) -> Option<String> {
+    let mut in_entry_group = false;
+    let mut terminal = false;
+    let mut exec = None;
+
+    for line in desktop_entry.lines() {
+        let line = line.trim();
+
+        if line.starts_with('[') {
+            in_entry_group = line == "[Desktop Entry]";
+            continue;
+        }
+
+        if !in_entry_group {
+            continue;
+        }
+
+        match line
+            .split_once('=')
+            .map(|(key, value)| (key.trim(), value.trim()))
+        {
+            Some(("Terminal", value)) => terminal = value == "true",
+            Some(("Exec", value)) => exec = Some(value),
+            _ => {},
+        }
+    }
+
+    if !terminal {
+        return None;
+    }
+
+    // `env VARIABLE=value program` starts the program.
+    let words = split_words(exec?).ok()?;
+    let program = words
+        .iter()
+        .find(|word| *word != "env" && !word.contains('='))?;
+
+    Path::new(program)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsStr;
+
+    use super::*;
+
+    fn words(command: &Command) -> Vec<&OsStr> {
+        once(command.get_program())
+            .chain(command.get_args())
+            .collect()
+    }
+
+    fn htop() -> Command {
+        let mut command = Command::new("htop");
+        command.args(["-d", "10"]);
+        command
+    }
+
+    #[test]
+    fn the_command_replaces_the_placeholder() {
+        let template = TerminalTemplate::parse("alacritty --class 'r menu' -e {cmd}").unwrap();
+
+        assert_eq!(
+            words(&template.wrap(&htop())),
+            ["alacritty", "--class", "r menu", "-e", "htop", "-d", "10"]
+        );
+    }
+
+    #[test]
+    fn the_command_is_quoted_inside_words_and_appended_without_placeholder() {
+        let template = TerminalTemplate::parse(r#"st -e sh -c "{cmd}; read""#).unwrap();
+        let mut command = Command::new("less");
+        command.arg("my notes");
+
+        assert_eq!(
+            words(&template.wrap(&command)),
+            ["st", "-e", "sh", "-c", "less 'my notes'; read"]
+        );
+
+        let template = TerminalTemplate::parse("kitty").unwrap();
+        assert_eq!(words(&template.wrap(&htop())), ["kitty", "htop", "-d", "10"]);
+    }
+
+    #[test]
+    fn desktop_entries_name_their_terminal_programs() {
+        let entry = "[Desktop Entry]\nName=htop\nExec=env TERM=xterm /usr/bin/htop \
+                     %U\nTerminal=true\n[Desktop Action New]\nTerminal=false\n";
+
+        assert_eq!(terminal_program(entry), Some("htop".to_string()));
+        assert_eq!(terminal_program("[Desktop Entry]\nExec=firefox %u\nTerminal=false\n"), None);
+    }
+}
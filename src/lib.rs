@@ -0,0 +1,9 @@
+pub mod cli;
+pub mod completions;
+pub mod components;
+pub mod config;
+pub mod launch;
+pub mod menu;
+pub mod modes;
+pub mod pinentry;
+pub mod utils;
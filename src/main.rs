@@ -1,30 +1,69 @@
-use std::process::{Command, Stdio};
+use std::env::args;
+use std::io::{Write, stdin, stdout};
+use std::iter::once;
+use std::process::exit;
 
-use completions::path::get_path_programs;
-use components::match_selector::pager::Pager;
-use components::text_input::TextInput;
-use config::loader::Config;
-use config::types::WindowPosition;
 use flexi_logger::{Logger, colored_default_format};
-use log::{error, info, warn};
-use sdl2::event::Event;
-use sdl2::gfx::primitives::DrawRenderer;
-use sdl2::init as sdl2_init;
-use sdl2::keyboard::Keycode;
-use sdl2::pixels::Color;
+use log::{debug, error, info, warn};
+use rmenu::cli::{CliOptions, USAGE};
+use rmenu::completions::stdin::get_stdin_entries;
+use rmenu::components::component::{Component, EventResult};
+use rmenu::components::container::{Container, Length};
+use rmenu::components::error_bar::ErrorBar;
+use rmenu::components::match_selector::pager::Pager;
+use rmenu::components::mode_bar::ModeBar;
+use rmenu::components::prompt::Prompt;
+use rmenu::components::text_input::TextInput;
+use rmenu::components::texture_cache::TextureCache;
+use rmenu::config::keybindings::KeyChord;
+use rmenu::config::loader::Config;
+use rmenu::config::types::{CaseSensitivity, WindowPosition};
+use rmenu::menu::state::{MenuInput, MenuOutcome, MenuState, QueryMode, Selection};
+use rmenu::modes::list::ListMode;
+use rmenu::modes::window::WindowAction;
+use rmenu::modes::{Mode, ModeError, ModeSwitcher, modes_by_name};
+use rmenu::pinentry::{PinDialog, PinRequest, serve};
+use rmenu::utils::display_scale::{detect_display_scale, scale_u32};
+use rmenu::utils::errors::handle_app_error;
+use rmenu::utils::fonts::{FontSet, load_default_font};
+use rmenu::utils::keycode_to_char::keycode_to_char;
+use rmenu::utils::misc::{find_mouse_monitor, ttf_context};
+use rmenu::utils::vector_matrix::{Vector2I, Vector2U};
+use sdl2::clipboard::ClipboardUtil;
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{Keycode, Mod};
+use sdl2::mouse::MouseButton;
 use sdl2::rect::Rect;
-use sdl2::rwops::RWops;
+use sdl2::render::WindowCanvas;
 use sdl2::version::version as sdl2_version;
-use utils::errors::handle_app_error;
-use utils::misc::{find_mouse_monitor, ttf_context};
-use utils::vector_matrix::{Vector2I, Vector2U};
-
-mod completions;
-mod components;
-mod config;
-mod utils;
+use sdl2::video::Window;
+use sdl2::{Sdl, VideoSubsystem, init as sdl2_init};
+use zeroize::Zeroize;
 
 fn main() {
+    let mut args = args();
+    let program = args
+        .next()
+        .unwrap_or_default();
+
+    let options = match CliOptions::for_program(&program, args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}\n\n{USAGE}");
+            exit(2);
+        },
+    };
+
+    if options.help() {
+        println!("{USAGE}");
+        return;
+    }
+
+    if options.version() {
+        println!("rmenu {}", env!("CARGO_PKG_VERSION"));
+        return;
+    }
+
     Logger::try_with_str("DEBUG")
         .expect("To start logger with DEBUG.")
         .format(colored_default_format)
@@ -34,96 +73,74 @@ fn main() {
     info!("Staring r-menu version {}", env!("CARGO_PKG_VERSION"));
 
     let sdl_context = handle_app_error!(sdl2_init());
-    let ttf_context = handle_app_error!(ttf_context());
+    handle_app_error!(ttf_context());
 
     info!("Initialized SDL2 {}", sdl2_version());
 
     // TODO: find the screen id instead of the window id.
     let video_subsystem = handle_app_error!(sdl_context.video());
-    let display_bounds = handle_app_error!(video_subsystem.display_bounds({
-        let monitor_id =
-            handle_app_error!(find_mouse_monitor(&video_subsystem)).unwrap_or_else(|| {
-                warn!("Couldn't get which monitor the mouse is in, falling back to 0");
-                0
-            });
-
-        info!("Detected monitor id {monitor_id}");
+    let monitor_id = handle_app_error!(find_mouse_monitor(&video_subsystem)).unwrap_or_else(|| {
+        warn!("Couldn't get which monitor the mouse is in, falling back to 0");
+        0
+    });
 
-        monitor_id
-    }));
+    info!("Detected monitor id {monitor_id}");
 
-    let default_font = handle_app_error!(ttf_context.load_font_from_rwops(
-        handle_app_error!(RWops::from_bytes(include_bytes!("../assets/default_font.ttf"))),
-        14
-    ));
-
-    macro_rules! window {
-        ([$x:expr, $y:expr], [$width:expr, $height:expr]) => {
-            handle_app_error!(
-                video_subsystem
-                    .window("r-menu", $width, $height)
-                    .position($x, $y)
-                    .borderless()
-                    .always_on_top()
-                    .build()
-            )
-            .into_canvas()
-            .present_vsync()
-            .build()
-            .map_err(|e| e.to_string())
-        };
-    }
+    let display_bounds = handle_app_error!(video_subsystem.display_bounds(monitor_id));
+    let detected_scale = detect_display_scale(&video_subsystem, monitor_id);
 
-    let config = match Config::load() {
+    let config = match Config::load().and_then(|mut config| {
+        config.apply_scale(detected_scale)?;
+        Ok(config)
+    }) {
         Ok(config) => config,
         Err(err) => {
             // This branch opens a window with default configuration,
             // and this window is used to display a configuration error,
             // so the user doesn't need to checkout logs every time.
 
-            // The window has similar properties to dmenu.
+            let default_font = handle_app_error!(
+                load_default_font(scale_u32(14, detected_scale) as u16)
+                    .map(|font| FontSet::new(vec![font]).expect("The default font to be loaded."))
+            );
 
-            let mut canvas = handle_app_error!(window!(
-                [display_bounds.x(), display_bounds.y()],
-                [display_bounds.width(), 20]
+            let mut canvas = handle_app_error!(menu_window(
+                &video_subsystem,
+                Rect::new(
+                    display_bounds.x(),
+                    display_bounds.y(),
+                    display_bounds.width(),
+                    scale_u32(20, detected_scale)
+                )
             ));
 
             warn!("Error detected. Error window opened");
             error!("{err:#}");
 
+            let mut error_bar = ErrorBar::new(&default_font, &format!("{err:#}"));
+            error_bar.set_scale(detected_scale);
+
             let texture_creator = canvas.texture_creator();
-            let error_text_surface = handle_app_error!(
-                default_font
-                    .render(&format!("{err:#} | Press <ESC> or <RETURN> to exit."))
-                    .blended(Color::RED)
-            );
-            let error_text_texture =
-                handle_app_error!(texture_creator.create_texture_from_surface(&error_text_surface));
+            let mut texture_cache = TextureCache::new(&texture_creator);
 
             let mut event_pump = handle_app_error!(sdl_context.event_pump());
-            'event_loop: loop {
-                for event in event_pump.poll_iter() {
-                    if let Event::KeyDown {
-                        keycode: Some(Keycode::Escape | Keycode::Return),
-                        ..
-                    } = event
-                    {
-                        break 'event_loop;
-                    }
-                }
-
-                canvas.set_draw_color(Color::RGB(20, 20, 20));
+            loop {
+                canvas.set_draw_color(ErrorBar::BACKGROUND_COLOR);
                 canvas.clear();
 
-                handle_app_error!(canvas.filled_circle(10, 10, 5, Color::RED));
-
-                handle_app_error!(canvas.copy(
-                    &error_text_texture,
-                    None,
-                    Some(Rect::new(25, 0, error_text_surface.width(), error_text_surface.height()))
-                ));
+                handle_app_error!(error_bar.draw(&mut canvas, &mut texture_cache));
 
                 canvas.present();
+
+                // Nothing changes in this window, it's
+                // only drawn again after an event.
+                if let Event::KeyDown {
+                    keycode: Some(Keycode::Escape | Keycode::Return),
+                    ..
+                } = event_pump.wait_event()
+                {
+                    break;
+                }
             }
 
             info!("See ya!");
@@ -131,10 +148,6 @@ fn main() {
         },
     };
 
-    let font = config
-        .font()
-        .unwrap_or(&default_font);
-
     let window_rect = {
         let config_padding = config.window_padding();
         let config_height = config.window_height();
@@ -157,173 +170,523 @@ fn main() {
         Rect::new(window_position.x(), window_position.y(), window_size.x(), window_size.y())
     };
 
-    let mut canvas = handle_app_error!(window!(
-        [window_rect.x(), window_rect.y()],
-        [window_rect.width(), window_rect.height()]
-    ));
+    if options.pinentry() {
+        handle_app_error!(serve(stdin().lock(), stdout().lock(), |dialog, request| {
+            pin_dialog(&sdl_context, &config, window_rect, dialog, request)
+        }));
+
+        info!("Pinentry closed, cheerio.");
+        return;
+    }
+
+    // As dmenu, the input is a query for the lines read, otherwise
+    // the modes are switched between, the first one is shown.
+    let modes: Vec<Box<dyn Mode>> = if options.password() {
+        let mut state = MenuState::in_order(Vec::new());
+        state.set_secret(true);
+
+        vec![Box::new(ListMode::new("password", state))]
+    } else if options.dmenu() {
+        let mut state = MenuState::in_order(handle_app_error!(get_stdin_entries(stdin().lock())));
+        state.set_query_mode(QueryMode::Query);
+        state.set_multi_select(options.multi_select());
+
+        vec![Box::new(ListMode::new("dmenu", state))]
+    } else {
+        let names = if !options
+            .modes()
+            .is_empty()
+        {
+            options
+                .modes()
+                .to_vec()
+        } else if let Some(script) = options.script() {
+            vec![script.to_string()]
+        } else if !config
+            .modes()
+            .is_empty()
+        {
+            config
+                .modes()
+                .to_vec()
+        } else {
+            vec!["run".to_string()]
+        };
+
+        let window_action =
+            if options.close_window() { WindowAction::Close } else { WindowAction::Activate };
+
+        match modes_by_name(&names, &config, window_action) {
+            Ok(modes) => modes,
+            Err(err) => {
+                error!("{err}");
+                exit(2);
+            },
+        }
+    };
+
+    let switcher = handle_app_error!(ModeSwitcher::new(modes, |state| {
+        configure_state(state, &config, &options)
+    }));
+
+    let prints = options.dmenu() || options.password();
+
+    // Programs are started before the menu closes, so it can stay
+    // open showing why one couldn't be started, scripts are run
+    // again and may list new entries to choose from.
+    let selection = run_menu(&sdl_context, &config, window_rect, options.prompt(), switcher);
+
+    match selection {
+        Some(selection) if prints => print_selection(&selection, options.print0()),
+
+        // Like dmenu, scripts can tell a dismissed menu apart.
+        None if prints => exit(1),
+        _ => {},
+    }
+}
+
+// How the candidates are matched, as configured.
+fn configure_state(state: &mut MenuState, config: &Config, options: &CliOptions) {
+    state.set_case_sensitivity(if options.case_insensitive() {
+        CaseSensitivity::Insensitive
+    } else {
+        config.case_sensitivity()
+    });
+    state.set_matcher(config.matcher());
+}
+
+// Opens the menu window until it's accepted or dismissed, what's
+// accepted is given to the mode shown, the menu stays open if it
+// fails, and shows the entries it lists instead, if it lists any.
+fn run_menu(
+    sdl_context: &Sdl,
+    config: &Config,
+    window_rect: Rect,
+    prompt: Option<&str>,
+    mut switcher: ModeSwitcher,
+) -> Option<Selection> {
+    let font = config
+        .fonts()
+        .expect("Fonts to be loaded by apply_scale.");
+
+    let video_subsystem = handle_app_error!(sdl_context.video());
+    let mut canvas = handle_app_error!(menu_window(&video_subsystem, window_rect));
 
     info!("Started window, requested: {window_rect:?}");
 
     let texture_creator = canvas.texture_creator();
 
-    let mut input = TextInput::new(&font);
-    input.set_color(config.text_color());
-    input.set_position(Vector2I::new(0, 0));
+    // The prompt given is shown over the one the mode asks for,
+    // which changes as modes are switched or scripts list entries.
+    let mode_prompt = |switcher: &ModeSwitcher| {
+        prompt
+            .or(switcher.prompt())
+            .or(config.prompt())
+            .map(str::to_string)
+    };
 
-    let minus_a_quarter_window = (window_rect.width() / 2) / 2;
+    let mut shown_prompt = mode_prompt(&switcher);
+    let secret = switcher
+        .state()
+        .is_secret();
+
+    let mut root = menu_root(font, config, shown_prompt.as_deref(), secret, window_rect.width());
+
+    // The modes are named at the right of the window,
+    // unless there is only one to switch to.
+    let mut mode_bar = (switcher
+        .names()
+        .len()
+        > 1)
+    .then(|| {
+        let mut mode_bar = ModeBar::new(
+            font,
+            switcher
+                .names()
+                .map(str::to_string)
+                .collect(),
+        );
+        mode_bar.set_text_color(config.text_color());
+        mode_bar.set_highlight_color(config.highlight_color());
+        mode_bar.set_highlighted_text_color(config.highlighted_text_color());
+        mode_bar.set_scale(config.scale());
+        mode_bar
+    });
+
+    let mode_bar_width = match &mode_bar {
+        Some(mode_bar) => handle_app_error!(mode_bar.width()).min(window_rect.width() / 2),
+        None => 0,
+    };
 
-    let mut pager = Pager::new(
-        handle_app_error!(get_path_programs())
-            .into_iter()
-            .collect(),
-        &font,
-    );
-    pager.set_position(Vector2I::new(minus_a_quarter_window as i32, 0));
-    pager.set_size(Vector2U::new(
-        window_rect.width() - minus_a_quarter_window,
-        window_rect.height(),
-    ));
-    pager.set_text_color(config.text_color());
-    pager.set_highlight_color(config.highlight_color());
-    pager.set_highlighted_text_color(config.highlighted_text_color());
-    handle_app_error!(pager.compute_text(""));
+    let root_bounds = Rect::new(0, 0, window_rect.width() - mode_bar_width, window_rect.height());
+    let mode_bar_bounds =
+        Rect::new(root_bounds.width() as i32, 0, mode_bar_width, window_rect.height());
+
+    handle_app_error!(root.layout(root_bounds, switcher.state_mut()));
+
+    if let Some(mode_bar) = &mut mode_bar {
+        handle_app_error!(Component::<Window>::layout(
+            mode_bar,
+            mode_bar_bounds,
+            switcher.state_mut()
+        ));
+    }
 
-    let mut shift_pressed = false;
-    let mut in_args = false;
+    let clipboard = video_subsystem.clipboard();
+
+    let mut texture_cache = TextureCache::new(&texture_creator);
+
+    let mut accepted = None;
+    let mut needs_redraw = true;
+    let mut frames_rendered: u64 = 0;
 
     let mut event_pump = handle_app_error!(sdl_context.event_pump());
     'event_loop: loop {
-        for event in event_pump.poll_iter() {
-            match event {
-                Event::Quit { .. } => break 'event_loop,
+        if needs_redraw {
+            canvas.set_draw_color(config.window_background_color());
+            canvas.clear();
 
-                Event::KeyDown { keycode: Some(keycode), .. } => match keycode {
-                    Keycode::Escape => {
-                        info!("Cheerio.");
-                        break 'event_loop;
-                    },
+            handle_app_error!(root.draw(switcher.state(), &mut canvas, &mut texture_cache));
 
-                    Keycode::LShift | Keycode::RShift => {
-                        shift_pressed = true;
-                    },
+            if let Some(mode_bar) = &mode_bar {
+                handle_app_error!(mode_bar.draw(switcher.state(), &mut canvas, &mut texture_cache));
+            }
 
-                    Keycode::Tab => {
-                        if !in_args {
-                            if let Some(selected) = pager.get_selected_entry() {
-                                input.set_text(
-                                    selected
-                                        .item()
-                                        .get_text(),
-                                );
-                            }
-                        }
-                    },
+            texture_cache.finish_frame();
+            canvas.present();
 
-                    Keycode::Return => {
-                        let input_args = input.get_args();
+            frames_rendered += 1;
+            debug!("Rendered frame {frames_rendered}");
 
-                        let mut command = match pager.get_selected_entry() {
-                            Some(selected) if input_args.len() <= 1 => {
-                                info!(
-                                    "Requesting to start '{}'",
-                                    selected
-                                        .item()
-                                        .get_text()
-                                );
-                                Command::new(
-                                    selected
-                                        .item()
-                                        .get_text(),
-                                )
-                            },
+            needs_redraw = false;
+        }
 
-                            _ => {
-                                if input_args.is_empty() {
-                                    continue;
-                                }
+        // Blocks until something happens, so an idle launcher
+        // doesn't use any CPU, then handles what else is queued.
+        let first_event = event_pump.wait_event();
+
+        for event in once(first_event).chain(event_pump.poll_iter()) {
+            let consumed = match &mut mode_bar {
+                Some(mode_bar) => handle_app_error!(Component::<Window>::handle_event(
+                    mode_bar,
+                    &event,
+                    switcher.state_mut()
+                )),
+                None => EventResult::Ignored,
+            };
+
+            let consumed = match consumed {
+                EventResult::Ignored => {
+                    handle_app_error!(root.handle_event(&event, switcher.state_mut()))
+                },
+                consumed => consumed,
+            };
 
-                                let mut command = Command::new(&input_args[0]);
+            // Hovering the window doesn't change anything.
+            needs_redraw |=
+                !matches!((&consumed, &event), (EventResult::Ignored, Event::MouseMotion { .. }));
 
-                                if input_args.len() > 1 {
-                                    command.args(&input_args[1..]);
-                                }
+            let outcome = match (consumed, event) {
+                (EventResult::Consumed(outcome), _) => outcome,
 
-                                info!("Requesting to start '{}'", input_args.join(" "));
+                (_, Event::Quit { .. }) => MenuOutcome::Cancel,
 
-                                command
-                            },
-                        };
+                (_, Event::KeyDown { keycode: Some(keycode), keymod, .. }) => {
+                    let chord = KeyChord::new(keycode, keymod);
 
-                        command.stdout(Stdio::null());
-                        command.stderr(Stdio::null());
-                        command.stdin(Stdio::null());
+                    let outcome = match config
+                        .keybindings()
+                        .action_for(&chord)
+                    {
+                        Some(action) => switcher
+                            .state_mut()
+                            .handle(MenuInput::Action(action)),
+
+                        None => match keycode_to_char(
+                            keycode,
+                            keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD),
+                        ) {
+                            Some(letter) if !chord.has_command_modifier() => switcher
+                                .state_mut()
+                                .handle(MenuInput::Char(letter)),
+                            _ => MenuOutcome::Continue,
+                        },
+                    };
+
+                    // Like X11 terminals, selecting text
+                    // replaces the primary selection.
+                    if let Some(selected) = switcher
+                        .state()
+                        .editor()
+                        .selected_text()
+                        && let Err(err) = clipboard.set_primary_selection_text(&selected)
+                    {
+                        warn!("Couldn't set the primary selection: {err}");
+                    }
 
-                        #[cfg(unix)]
-                        unsafe {
-                            use std::os::unix::process::CommandExt;
+                    outcome
+                },
 
-                            command.pre_exec(|| {
-                                sdl2::libc::setsid();
-                                Ok(())
-                            });
-                        }
+                (_, Event::Window { win_event: WindowEvent::FocusLost, .. })
+                    if config.dismiss_on_focus_loss() =>
+                {
+                    info!("Focus lost, cheerio.");
+                    break 'event_loop;
+                },
 
-                        #[cfg(windows)]
-                        {
-                            use std::os::windows::process::CommandExt;
+                (_, Event::MouseButtonDown { mouse_btn: MouseButton::Middle, .. }) => {
+                    MenuOutcome::Paste { primary: true }
+                },
 
-                            command.creation_flags(0x00000008);
-                        }
+                _ => MenuOutcome::Continue,
+            };
 
-                        handle_app_error!(command.spawn());
+            match outcome {
+                MenuOutcome::Continue => {},
 
-                        info!("Started gracefully... Have a jolly good day!");
+                MenuOutcome::Cancel => {
+                    info!("Cheerio.");
+                    break 'event_loop;
+                },
 
+                MenuOutcome::Accept(selection) => match switcher.accept(&selection) {
+                    Ok(true) => {
+                        accepted = Some(selection);
                         break 'event_loop;
                     },
+                    Ok(false) => {},
+                    Err(err) => show_mode_error(&mut switcher, &err),
+                },
 
-                    keycode => {
-                        if input.is_caret_at_end() {
-                            pager.keycode_interaction(keycode);
-                        }
-
-                        if pager.is_caret_at_start() {
-                            input.keycode_interaction(keycode);
-                        }
+                MenuOutcome::CycleMode { forward } => {
+                    if let Err(err) = switcher.cycle(forward) {
+                        show_mode_error(&mut switcher, &err);
+                    }
+                },
 
-                        input.act_char_at_caret(keycode, shift_pressed);
+                MenuOutcome::ShowMode(index) => {
+                    if let Err(err) = switcher.show(index) {
+                        show_mode_error(&mut switcher, &err);
+                    }
+                },
 
-                        let input_args = input.get_args();
+                MenuOutcome::Copy(text) => {
+                    if let Err(err) = clipboard.set_clipboard_text(&text) {
+                        warn!("Couldn't copy to the clipboard: {err}");
+                    }
+                },
 
-                        if let Some(program_name) = input_args.get(0) {
-                            handle_app_error!(pager.compute_text(program_name));
-                        }
+                MenuOutcome::PrimarySelection(text) => {
+                    if let Err(err) = clipboard.set_primary_selection_text(&text) {
+                        warn!("Couldn't set the primary selection: {err}");
+                    }
+                },
 
-                        in_args = input_args.len() > 1;
-                    },
+                MenuOutcome::Paste { primary } => {
+                    if let Some(mut text) = clipboard_text(&clipboard, primary) {
+                        switcher
+                            .state_mut()
+                            .handle(MenuInput::Paste(&text));
+                        text.zeroize();
+                    }
                 },
+            }
 
-                Event::KeyUp { keycode: Some(keycode), .. } => match keycode {
-                    Keycode::LShift | Keycode::RShift => {
-                        shift_pressed = false;
-                    },
+            if let Some(mode_bar) = &mut mode_bar {
+                mode_bar.set_active(switcher.active());
+            }
 
-                    _ => {},
-                },
+            // Each mode can ask for its own prompt, and scripts
+            // can ask for another one with their new entries.
+            let next_prompt = mode_prompt(&switcher);
 
-                _ => {},
+            if next_prompt != shown_prompt {
+                shown_prompt = next_prompt;
+                root =
+                    menu_root(font, config, shown_prompt.as_deref(), secret, window_rect.width());
             }
+
+            // Anything that changed the input, typed or pasted, filters
+            // the matches, which have to be laid out in pages again.
+            handle_app_error!(root.layout(root_bounds, switcher.state_mut()));
         }
+    }
+
+    debug!("Rendered {frames_rendered} frames in total");
+
+    accepted
+}
+
+// The prompt, if there is one, the input and the matches, unless
+// the input is a password, as nothing is matched against it.
+fn menu_root<'f>(
+    font: &'f FontSet<'f>,
+    config: &Config,
+    prompt: Option<&str>,
+    secret: bool,
+    window_width: u32,
+) -> Container<'f, Window> {
+    let mut input = TextInput::new(font);
+    input.set_color(config.text_color());
+    input.set_selection_color(config.highlight_color());
+    input.set_mask(config.password_mask());
+    input.set_scale(config.scale());
+
+    let mut root = Container::horizontal();
+    let mut prompt_width = 0;
+
+    // The input starts after the prompt, if there is one.
+    if let Some(prompt_text) = prompt {
+        let mut prompt = Prompt::new(font, prompt_text);
+        prompt.set_text_color(config.prompt_text_color());
+        prompt.set_background_color(config.prompt_background_color());
+        prompt.set_scale(config.scale());
+
+        prompt_width = handle_app_error!(prompt.width());
+        root = root.with(Length::Fixed(prompt_width), prompt);
+    }
+
+    if secret {
+        return root.with(Length::Fill, input);
+    }
 
-        canvas.set_draw_color(config.window_background_color());
-        canvas.clear();
+    let mut pager = Pager::new(font);
+    pager.set_text_color(config.text_color());
+    pager.set_highlight_color(config.highlight_color());
+    pager.set_highlighted_text_color(config.highlighted_text_color());
+    pager.set_match_color(config.match_color());
+    pager.set_mark_color(config.mark_color());
+    pager.set_urgent_color(config.urgent_color());
+    pager.set_scale(config.scale());
+    pager.set_launch_clicks(
+        config
+            .mouse_launch()
+            .clicks(),
+    );
+
+    let input_width = TextInput::width_beside_pager(window_width, prompt_width);
+
+    root.with(Length::Fixed(input_width), input)
+        .with(Length::Fill, pager)
+}
+
+// Logs why a mode failed and shows it instead of the matches.
+fn show_mode_error(switcher: &mut ModeSwitcher, err: &ModeError) {
+    error!("{err:#}");
+
+    switcher
+        .state_mut()
+        .set_launch_error(err.to_string());
+}
+
+// A window with similar properties to dmenu's.
+fn menu_window(video_subsystem: &VideoSubsystem, bounds: Rect) -> Result<WindowCanvas, String> {
+    video_subsystem
+        .window("r-menu", bounds.width(), bounds.height())
+        .position(bounds.x(), bounds.y())
+        .borderless()
+        .always_on_top()
+        .build()
+        .map_err(|err| err.to_string())?
+        .into_canvas()
+        .present_vsync()
+        .build()
+        .map_err(|err| err.to_string())
+}
+
+// Shows a pinentry dialog, PINs are typed as a password and
+// confirmations choose between the ok and cancel labels.
+fn pin_dialog(
+    sdl_context: &Sdl,
+    config: &Config,
+    window_rect: Rect,
+    dialog: PinDialog,
+    request: &PinRequest,
+) -> Option<String> {
+    let label = request.label();
+    let prompt = (!label.is_empty()).then_some(label.as_str());
+
+    if dialog == PinDialog::GetPin {
+        let mut state = MenuState::in_order(Vec::new());
+        state.set_secret(true);
+
+        return run_menu(sdl_context, config, window_rect, prompt, single_mode(state)).map(
+            |selection| {
+                selection
+                    .input()
+                    .to_string()
+            },
+        );
+    }
+
+    let mut buttons = vec![
+        request
+            .ok()
+            .to_string(),
+    ];
+
+    if dialog == PinDialog::Confirm {
+        buttons.push(
+            request
+                .cancel()
+                .to_string(),
+        );
+    }
 
-        handle_app_error!(input.draw(&mut canvas, &texture_creator));
+    let mut state = MenuState::in_order(buttons);
+    state.set_query_mode(QueryMode::Query);
 
-        if !in_args {
-            handle_app_error!(pager.draw(&mut canvas));
+    run_menu(sdl_context, config, window_rect, prompt, single_mode(state))
+        .filter(|selection| selection.entry() == Some(request.ok()))
+        .map(|_| String::new())
+}
+
+// A menu showing the given entries, as they are.
+fn single_mode(state: MenuState) -> ModeSwitcher<'static> {
+    handle_app_error!(ModeSwitcher::new(vec![Box::new(ListMode::new("pinentry", state))], |_| {}))
+}
+
+// The clipboard text, or the primary selection, if there is any,
+// nothing is pasted if it can't be read.
+fn clipboard_text(clipboard: &ClipboardUtil, primary: bool) -> Option<String> {
+    let text = if primary {
+        if !clipboard.has_primary_selection_text() {
+            return None;
+        }
+
+        clipboard.primary_selection_text()
+    } else {
+        if !clipboard.has_clipboard_text() {
+            return None;
         }
 
-        canvas.present();
+        clipboard.clipboard_text()
+    };
+
+    text.inspect_err(|err| warn!("Couldn't read the clipboard: {err}"))
+        .ok()
+}
+
+// Prints the marked entries, or the selected one or what's typed.
+fn print_selection(selection: &Selection, print0: bool) {
+    let terminator = if print0 { '\0' } else { '\n' };
+
+    let mut stdout = stdout().lock();
+
+    if selection
+        .marked()
+        .is_empty()
+    {
+        let entry = selection
+            .entry()
+            .unwrap_or(selection.input());
+
+        handle_app_error!(write!(stdout, "{entry}{terminator}"));
+    } else {
+        for entry in selection.marked() {
+            handle_app_error!(write!(stdout, "{entry}{terminator}"));
+        }
     }
+
+    handle_app_error!(stdout.flush());
 }
@@ -0,0 +1,727 @@
+use std::cmp::min;
+use std::mem::replace;
+
+use zeroize::Zeroize;
+
+const MAX_UNDO_STEPS: usize = 100;
+const MAX_KILL_RING_ENTRIES: usize = 32;
+
+// What secret buffers reserve up front, past it they're grown by
+// `reserve_secret` so no copy of the text is left in freed memory.
+const SECRET_CAPACITY: usize = 256;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum EditKind {
+    Insert,
+    Remove,
+    Kill,
+    Yank,
+    Other,
+}
+
+struct Snapshot {
+    buffer: Vec<char>,
+    caret_position: u16,
+}
+
+/// The text typed in the menu, a single line with a caret,
+/// selection, undo history and an emacs like kill ring.
+#[derive(Default)]
+pub struct LineEditor {
+    caret_position: u16,
+    buffer: Vec<char>,
+
+    undo_stack: Vec<Snapshot>,
+    redo_stack: Vec<Snapshot>,
+    last_edit: Option<EditKind>,
+
+    kill_ring: Vec<String>,
+    kill_ring_index: usize,
+    last_yank: Option<(usize, usize)>,
+
+    // Where the selection started, the
+    // caret is the other selection end.
+    selection_anchor: Option<u16>,
+
+    secret: bool,
+}
+
+impl LineEditor {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Secret text, such as a password, isn't kept in the undo
+    /// history nor the kill ring, can't be copied and is zeroised
+    /// when the editor is dropped.
+    pub fn set_secret(&mut self, secret: bool) {
+        self.secret = secret;
+
+        if secret {
+            for snapshot in self
+                .undo_stack
+                .iter_mut()
+                .chain(&mut self.redo_stack)
+            {
+                snapshot
+                    .buffer
+                    .zeroize();
+            }
+
+            self.undo_stack
+                .clear();
+            self.redo_stack
+                .clear();
+            self.kill_ring
+                .zeroize();
+            self.buffer
+                .reserve(SECRET_CAPACITY);
+        }
+    }
+
+    #[inline]
+    pub const fn is_secret(&self) -> bool {
+        self.secret
+    }
+
+    // Records the state before an edit so it can be undone, consecutive
+    // edits of the same kind, such as typing a word, are undone together.
+    fn begin_edit(&mut self, kind: EditKind) {
+        if self.secret {
+            self.last_edit = Some(kind);
+            return;
+        }
+
+        if self.last_edit != Some(kind) || kind == EditKind::Other {
+            self.undo_stack
+                .push(self.snapshot());
+
+            if self
+                .undo_stack
+                .len()
+                > MAX_UNDO_STEPS
+            {
+                self.undo_stack
+                    .remove(0);
+            }
+        }
+
+        self.redo_stack
+            .clear();
+        self.last_edit = Some(kind);
+    }
+
+    #[inline]
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            buffer: self.buffer.clone(),
+            caret_position: self.caret_position,
+        }
+    }
+
+    #[inline]
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.buffer = snapshot.buffer;
+        self.caret_position = snapshot.caret_position;
+        self.selection_anchor = None;
+        self.last_edit = None;
+    }
+
+    // Moves the caret without recording an edit, so
+    // the next edit starts a new undo step.
+    #[inline]
+    fn move_caret(&mut self, position: usize) {
+        self.caret_position = min(position, self.buffer.len()) as u16;
+        self.selection_anchor = None;
+        self.last_edit = None;
+    }
+
+    /// Runs a caret movement extending the selection
+    /// instead of clearing it.
+    pub fn select_with(&mut self, movement: impl FnOnce(&mut Self)) {
+        let anchor = self
+            .selection_anchor
+            .unwrap_or(self.caret_position);
+
+        movement(self);
+
+        self.selection_anchor = (anchor != self.caret_position).then_some(anchor);
+    }
+
+    #[inline]
+    pub fn select_all(&mut self) {
+        self.move_caret(self.buffer.len());
+        self.selection_anchor = (!self
+            .buffer
+            .is_empty())
+        .then_some(0);
+    }
+
+    /// The selected range of characters, if any.
+    #[inline]
+    pub fn selection(&self) -> Option<(usize, usize)> {
+        self.selection_anchor
+            .map(|anchor| {
+                (
+                    min(anchor, self.caret_position) as usize,
+                    anchor.max(self.caret_position) as usize,
+                )
+            })
+    }
+
+    /// The selected text, unless the text is secret.
+    pub fn selected_text(&self) -> Option<String> {
+        if self.secret {
+            return None;
+        }
+
+        self.selection()
+            .map(|(start, end)| {
+                self.buffer[start..end]
+                    .iter()
+                    .collect()
+            })
+    }
+
+    // Removes the selected text, returning whether
+    // there was a selection to remove.
+    fn remove_selection(&mut self) -> bool {
+        let Some((start, end)) = self.selection() else {
+            return false;
+        };
+
+        self.buffer
+            .drain(start..end);
+        self.caret_position = start as u16;
+        self.selection_anchor = None;
+
+        true
+    }
+
+    #[inline]
+    pub fn caret(&self) -> usize {
+        self.caret_position as usize
+    }
+
+    // Makes room for more secret text, moving it to a bigger buffer
+    // and zeroising the old one instead of letting the vector grow.
+    fn reserve_secret(&mut self, additional: usize) {
+        let needed = self.buffer.len() + additional;
+
+        if !self.secret
+            || needed
+                <= self
+                    .buffer
+                    .capacity()
+        {
+            return;
+        }
+
+        let mut grown = Vec::with_capacity(
+            needed.max(
+                self.buffer
+                    .capacity()
+                    * 2,
+            ),
+        );
+        grown.extend_from_slice(&self.buffer);
+
+        let mut old = replace(&mut self.buffer, grown);
+        old.zeroize();
+    }
+
+    fn insert_str_at_caret(&mut self, text: &str) {
+        let text = text
+            .chars()
+            .take(u16::MAX as usize - self.buffer.len())
+            .collect::<Vec<char>>();
+
+        self.reserve_secret(text.len());
+
+        let caret = self.caret();
+        self.caret_position += text.len() as u16;
+        self.buffer
+            .splice(caret..caret, text);
+    }
+
+    #[inline]
+    pub fn insert_char_at_caret(&mut self, letter: char) {
+        if self
+            .selection_anchor
+            .is_some()
+        {
+            self.begin_edit(EditKind::Other);
+            self.remove_selection();
+        }
+
+        if self.buffer.len() < u16::MAX as usize {
+            self.begin_edit(if letter.is_whitespace() {
+                EditKind::Other
+            } else {
+                EditKind::Insert
+            });
+
+            self.reserve_secret(1);
+            self.buffer
+                .insert(self.caret(), letter);
+            self.caret_position += 1;
+        }
+    }
+
+    /// Inserts pasted text at the caret replacing the selection,
+    /// new lines are replaced by spaces as the input is a single line.
+    pub fn paste(&mut self, text: &str) {
+        let text = text
+            .replace("\r\n", " ")
+            .replace(['\n', '\r'], " ");
+
+        if text.is_empty() {
+            return;
+        }
+
+        self.begin_edit(EditKind::Other);
+        self.remove_selection();
+        self.insert_str_at_caret(&text);
+    }
+
+    #[inline]
+    pub fn remove_char_at_caret(&mut self) {
+        if self
+            .selection_anchor
+            .is_some()
+        {
+            self.begin_edit(EditKind::Other);
+            self.remove_selection();
+            return;
+        }
+
+        if self.caret_position > 0 {
+            self.begin_edit(EditKind::Remove);
+
+            self.buffer
+                .remove(self.caret() - 1);
+            self.caret_position -= 1;
+        }
+    }
+
+    #[inline]
+    pub fn remove_char_after_caret(&mut self) {
+        if self
+            .selection_anchor
+            .is_some()
+        {
+            self.begin_edit(EditKind::Other);
+            self.remove_selection();
+            return;
+        }
+
+        if self.caret() < self.buffer.len() {
+            self.begin_edit(EditKind::Remove);
+
+            self.buffer
+                .remove(self.caret());
+        }
+    }
+
+    #[inline]
+    pub fn advance_caret(&mut self) {
+        self.move_caret(self.caret() + 1);
+    }
+
+    #[inline]
+    pub fn retreat_caret(&mut self) {
+        self.move_caret(
+            self.caret()
+                .saturating_sub(1),
+        );
+    }
+
+    #[inline]
+    pub fn move_caret_to_start(&mut self) {
+        self.move_caret(0);
+    }
+
+    #[inline]
+    pub fn move_caret_to_end(&mut self) {
+        self.move_caret(self.buffer.len());
+    }
+
+    #[inline]
+    pub fn is_caret_at_end(&self) -> bool {
+        self.caret() == self.buffer.len()
+    }
+
+    // Words are runs of alphanumeric characters, like readline does.
+    fn previous_word_start(&self) -> usize {
+        let mut position = self.caret();
+
+        while position > 0 && !self.buffer[position - 1].is_alphanumeric() {
+            position -= 1;
+        }
+
+        while position > 0 && self.buffer[position - 1].is_alphanumeric() {
+            position -= 1;
+        }
+
+        position
+    }
+
+    fn next_word_end(&self) -> usize {
+        let mut position = self.caret();
+
+        while position < self.buffer.len() && !self.buffer[position].is_alphanumeric() {
+            position += 1;
+        }
+
+        while position < self.buffer.len() && self.buffer[position].is_alphanumeric() {
+            position += 1;
+        }
+
+        position
+    }
+
+    #[inline]
+    pub fn move_caret_word_left(&mut self) {
+        self.move_caret(self.previous_word_start());
+    }
+
+    #[inline]
+    pub fn move_caret_word_right(&mut self) {
+        self.move_caret(self.next_word_end());
+    }
+
+    // Removes the text between the caret and the position
+    // and stores it in the kill ring, consecutive kills are
+    // joined in the same entry like emacs does.
+    fn kill_to(&mut self, position: usize) {
+        let caret = self.caret();
+        if position == caret {
+            return;
+        }
+
+        let (start, end) = (min(caret, position), caret.max(position));
+        let was_killing = self.last_edit == Some(EditKind::Kill);
+
+        self.selection_anchor = None;
+
+        self.begin_edit(EditKind::Kill);
+
+        let mut killed = self
+            .buffer
+            .drain(start..end)
+            .collect::<String>();
+        self.caret_position = start as u16;
+
+        if self.secret {
+            killed.zeroize();
+            return;
+        }
+
+        match self
+            .kill_ring
+            .last_mut()
+        {
+            Some(last) if was_killing => {
+                if position < caret {
+                    last.insert_str(0, &killed);
+                } else {
+                    last.push_str(&killed);
+                }
+            },
+
+            _ => {
+                self.kill_ring
+                    .push(killed);
+
+                if self.kill_ring.len() > MAX_KILL_RING_ENTRIES {
+                    self.kill_ring
+                        .remove(0);
+                }
+            },
+        }
+
+        self.kill_ring_index = self.kill_ring.len() - 1;
+    }
+
+    /// Removes the whitespace separated word before the caret.
+    pub fn remove_word_at_caret(&mut self) {
+        let mut position = self.caret();
+
+        while position > 0 && self.buffer[position - 1].is_whitespace() {
+            position -= 1;
+        }
+
+        while position > 0 && !self.buffer[position - 1].is_whitespace() {
+            position -= 1;
+        }
+
+        self.kill_to(position);
+    }
+
+    #[inline]
+    pub fn remove_word_after_caret(&mut self) {
+        self.kill_to(self.next_word_end());
+    }
+
+    #[inline]
+    pub fn kill_to_start(&mut self) {
+        self.kill_to(0);
+    }
+
+    #[inline]
+    pub fn kill_to_end(&mut self) {
+        self.kill_to(self.buffer.len());
+    }
+
+    /// Inserts the last killed text at the caret.
+    pub fn yank(&mut self) {
+        let Some(text) = self
+            .kill_ring
+            .last()
+            .cloned()
+        else {
+            return;
+        };
+
+        self.begin_edit(EditKind::Other);
+        self.remove_selection();
+        self.kill_ring_index = self.kill_ring.len() - 1;
+
+        let start = self.caret();
+        self.insert_str_at_caret(&text);
+
+        self.last_yank = Some((start, self.caret()));
+        self.last_edit = Some(EditKind::Yank);
+    }
+
+    /// Replaces the text that was just yanked
+    /// with the previous kill ring entry.
+    pub fn yank_pop(&mut self) {
+        let Some((start, end)) = self.last_yank else { return };
+
+        if self.last_edit != Some(EditKind::Yank)
+            || self
+                .kill_ring
+                .is_empty()
+        {
+            return;
+        }
+
+        self.kill_ring_index = self
+            .kill_ring_index
+            .checked_sub(1)
+            .unwrap_or(self.kill_ring.len() - 1);
+
+        let text = self.kill_ring[self.kill_ring_index].clone();
+
+        self.buffer
+            .drain(start..end);
+        self.caret_position = start as u16;
+        self.insert_str_at_caret(&text);
+
+        self.last_yank = Some((start, self.caret()));
+    }
+
+    pub fn undo(&mut self) {
+        if let Some(snapshot) = self
+            .undo_stack
+            .pop()
+        {
+            self.redo_stack
+                .push(self.snapshot());
+            self.restore(snapshot);
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some(snapshot) = self
+            .redo_stack
+            .pop()
+        {
+            self.undo_stack
+                .push(self.snapshot());
+            self.restore(snapshot);
+        }
+    }
+
+    pub fn set_text(&mut self, text: &str) {
+        self.begin_edit(EditKind::Other);
+
+        self.buffer.clear();
+        self.caret_position = 0;
+        self.selection_anchor = None;
+        self.insert_str_at_caret(text);
+    }
+
+    /// Places the caret at a character index, if `extend`
+    /// the selection is extended up to that index.
+    pub fn set_caret(&mut self, position: usize, extend: bool) {
+        if extend {
+            self.select_with(|editor| editor.move_caret(position));
+        } else {
+            self.move_caret(position);
+        }
+    }
+
+    #[inline]
+    pub fn chars(&self) -> &[char] {
+        &self.buffer
+    }
+
+    #[inline]
+    pub fn text(&self) -> String {
+        self.buffer
+            .iter()
+            .collect()
+    }
+
+    pub fn get_args(&self) -> Vec<String> {
+        self.buffer
+            .iter()
+            .collect::<String>()
+            .split(' ')
+            .map(|a| a.to_string())
+            .collect::<Vec<String>>()
+    }
+}
+
+impl Drop for LineEditor {
+    fn drop(&mut self) {
+        if self.secret {
+            self.buffer
+                .zeroize();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor(text: &str) -> LineEditor {
+        let mut editor = LineEditor::new();
+        editor.paste(text);
+        editor
+    }
+
+    #[test]
+    fn typing_a_word_is_undone_at_once() {
+        let mut editor = LineEditor::new();
+
+        for letter in "ls -la".chars() {
+            editor.insert_char_at_caret(letter);
+        }
+
+        editor.undo();
+        assert_eq!(editor.text(), "ls ");
+
+        editor.undo();
+        editor.undo();
+        assert_eq!(editor.text(), "");
+
+        editor.redo();
+        assert_eq!(editor.text(), "ls");
+    }
+
+    #[test]
+    fn words_are_alphanumeric_runs() {
+        let mut editor = editor("git commit --amend");
+
+        editor.move_caret_word_left();
+        assert_eq!(editor.caret(), 13);
+
+        editor.move_caret_word_left();
+        editor.move_caret_word_right();
+        assert_eq!(editor.caret(), 10);
+    }
+
+    #[test]
+    fn consecutive_kills_are_yanked_together() {
+        let mut editor = editor("echo hello world");
+
+        editor.remove_word_at_caret();
+        editor.remove_word_at_caret();
+        assert_eq!(editor.text(), "echo ");
+
+        editor.yank();
+        assert_eq!(editor.text(), "echo hello world");
+    }
+
+    #[test]
+    fn yank_pop_cycles_the_kill_ring() {
+        let mut editor = editor("one two");
+
+        editor.remove_word_at_caret();
+        editor.move_caret_to_start();
+        editor.kill_to_end();
+
+        editor.yank();
+        assert_eq!(editor.text(), "one ");
+
+        editor.yank_pop();
+        assert_eq!(editor.text(), "two");
+    }
+
+    #[test]
+    fn typing_replaces_the_selection() {
+        let mut editor = editor("firefox");
+
+        editor.select_with(LineEditor::move_caret_word_left);
+        assert_eq!(editor.selected_text(), Some("firefox".to_string()));
+
+        editor.insert_char_at_caret('x');
+        assert_eq!(editor.text(), "x");
+        assert_eq!(editor.selection(), None);
+    }
+
+    #[test]
+    fn pasted_new_lines_become_spaces() {
+        let editor = editor("echo\r\nhi\nthere");
+
+        assert_eq!(editor.text(), "echo hi there");
+        assert_eq!(editor.get_args(), ["echo", "hi", "there"]);
+    }
+
+    #[test]
+    fn set_caret_can_extend_the_selection() {
+        let mut editor = editor("abcdef");
+
+        editor.set_caret(1, false);
+        editor.set_caret(4, true);
+
+        assert_eq!(editor.selection(), Some((1, 4)));
+        assert_eq!(editor.selected_text(), Some("bcd".to_string()));
+    }
+
+    #[test]
+    fn secret_text_has_no_history() {
+        let mut editor = LineEditor::new();
+        editor.set_secret(true);
+        editor.paste("hunter2 pass");
+
+        editor.kill_to_start();
+        editor.yank();
+        editor.undo();
+        assert_eq!(editor.text(), "");
+
+        editor.paste("hunter2");
+        editor.select_all();
+        assert_eq!(editor.selected_text(), None);
+    }
+
+    #[test]
+    fn secret_text_grows_past_the_reserved_capacity() {
+        let mut editor = LineEditor::new();
+        editor.set_secret(true);
+
+        let password = "correct horse battery staple ".repeat(10);
+        for letter in password.chars() {
+            editor.insert_char_at_caret(letter);
+        }
+        editor.paste(&password);
+
+        assert!(password.len() * 2 > SECRET_CAPACITY);
+        assert_eq!(editor.text(), password.repeat(2));
+    }
+}
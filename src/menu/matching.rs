@@ -0,0 +1,370 @@
+use std::ops::Range;
+
+use regex::{Error as RegexError, Regex, RegexBuilder};
+
+use crate::config::types::CaseSensitivity;
+
+// Lowercases a text keeping, for every byte of the result, the byte
+// offset of the character it came from, since lowercasing can change
+// the length of a character.
+fn fold(text: &str) -> (String, Vec<usize>) {
+    let mut folded = String::with_capacity(text.len());
+    let mut offsets = Vec::with_capacity(text.len() + 1);
+
+    for (offset, character) in text.char_indices() {
+        for lower in character.to_lowercase() {
+            folded.push(lower);
+            offsets.resize(folded.len(), offset);
+        }
+    }
+
+    offsets.push(text.len());
+
+    (folded, offsets)
+}
+
+impl CaseSensitivity {
+    /// Whether letter case is compared for a query.
+    pub fn is_sensitive_for(&self, query: &str) -> bool {
+        match self {
+            Self::Sensitive => true,
+            Self::Insensitive => false,
+            Self::Smart => query
+                .chars()
+                .any(char::is_uppercase),
+        }
+    }
+}
+
+/// Where a term has to be found in a candidate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Anchor {
+    /// Anywhere, `term`.
+    None,
+    /// At the start, `^term`.
+    Start,
+    /// At the end, `term$`.
+    End,
+    /// The whole candidate, `^term$`.
+    Exact,
+}
+
+/// A space separated part of a query.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Term {
+    text: String,
+    anchor: Anchor,
+    negated: bool,
+}
+
+impl Term {
+    /// Parses a term with fzf's extended syntax, `!` negates it,
+    /// a leading `^` and a trailing `$` anchor it.
+    pub fn parse(term: &str) -> Self {
+        let (negated, term) = match term.strip_prefix('!') {
+            Some(term) => (true, term),
+            None => (false, term),
+        };
+
+        let (start, term) = match term.strip_prefix('^') {
+            Some(term) => (true, term),
+            None => (false, term),
+        };
+
+        let (end, term) = match term.strip_suffix('$') {
+            Some(term) => (true, term),
+            None => (false, term),
+        };
+
+        Self {
+            text: term.to_string(),
+            anchor: match (start, end) {
+                (true, true) => Anchor::Exact,
+                (true, false) => Anchor::Start,
+                (false, true) => Anchor::End,
+                (false, false) => Anchor::None,
+            },
+            negated,
+        }
+    }
+
+    #[inline]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    #[inline]
+    pub const fn anchor(&self) -> Anchor {
+        self.anchor
+    }
+
+    #[inline]
+    pub const fn is_negated(&self) -> bool {
+        self.negated
+    }
+}
+
+/// A query where every term has to match, in any order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Query {
+    terms: Vec<Term>,
+}
+
+impl Query {
+    pub fn parse(query: &str) -> Self {
+        Self {
+            terms: query
+                .split_whitespace()
+                .map(Term::parse)
+                .filter(|term| !term.text.is_empty())
+                .collect(),
+        }
+    }
+
+    #[inline]
+    pub fn terms(&self) -> &[Term] {
+        &self.terms
+    }
+
+    /// The sorted byte ranges of the candidate the terms matched,
+    /// if all of them match, negated terms highlight nothing.
+    pub fn matches(
+        &self,
+        candidate: &str,
+        case_sensitivity: CaseSensitivity,
+    ) -> Option<Vec<Range<usize>>> {
+        let mut highlights = Vec::<Range<usize>>::new();
+
+        for term in &self.terms {
+            let found = find(candidate, &term.text, term.anchor, case_sensitivity);
+
+            match (found, term.negated) {
+                (Some(_), true) | (None, false) => return None,
+                (Some(range), false) => highlights.push(range),
+                (None, true) => {},
+            }
+        }
+
+        highlights.sort_by_key(|range| range.start);
+
+        // Terms can match overlapping parts.
+        let mut merged = Vec::<Range<usize>>::with_capacity(highlights.len());
+
+        for range in highlights {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => {
+                    last.end = last
+                        .end
+                        .max(range.end)
+                },
+                _ => merged.push(range),
+            }
+        }
+
+        Some(merged)
+    }
+}
+
+/// The part of the candidate a query matches as a prefix.
+pub fn match_prefix(
+    candidate: &str,
+    query: &str,
+    case_sensitivity: CaseSensitivity,
+) -> Option<Range<usize>> {
+    find(candidate, query, Anchor::Start, case_sensitivity)
+}
+
+/// Compiles a regex query, comparing case as configured.
+pub fn compile_regex(query: &str, case_sensitivity: CaseSensitivity) -> Result<Regex, String> {
+    RegexBuilder::new(query)
+        .case_insensitive(!case_sensitivity.is_sensitive_for(query))
+        .build()
+        .map_err(|err| match err {
+            // Syntax errors draw the pattern over many lines,
+            // the last one is what's wrong with it.
+            RegexError::Syntax(message) => message
+                .lines()
+                .last()
+                .unwrap_or_default()
+                .trim_start_matches("error: ")
+                .to_string(),
+            err => err.to_string(),
+        })
+}
+
+/// The byte ranges of the candidate a regex matched, if it matched.
+pub fn match_regex(candidate: &str, regex: &Regex) -> Option<Vec<Range<usize>>> {
+    if !regex.is_match(candidate) {
+        return None;
+    }
+
+    Some(
+        regex
+            .find_iter(candidate)
+            .map(|found| found.range())
+            .filter(|range| !range.is_empty())
+            .collect(),
+    )
+}
+
+// Finds a term in a candidate comparing case as configured,
+// returning the byte range of the candidate it covers.
+fn find(
+    candidate: &str,
+    term: &str,
+    anchor: Anchor,
+    case_sensitivity: CaseSensitivity,
+) -> Option<Range<usize>> {
+    if case_sensitivity.is_sensitive_for(term) {
+        let start = find_start(candidate, term, anchor)?;
+        return Some(start..start + term.len());
+    }
+
+    let (folded_candidate, offsets) = fold(candidate);
+    let (folded_term, _) = fold(term);
+
+    let start = find_start(&folded_candidate, &folded_term, anchor)?;
+    let end = start + folded_term.len();
+
+    if folded_term.is_empty() {
+        return Some(offsets[start]..offsets[start]);
+    }
+
+    // A match ending inside a character that lowercases to
+    // many covers that whole character of the candidate.
+    let last_matched = offsets[end - 1];
+    let end = offsets[end..]
+        .iter()
+        .copied()
+        .find(|offset| *offset > last_matched)
+        .unwrap_or(candidate.len());
+
+    Some(offsets[start]..end)
+}
+
+// Where the term starts in the text, if it's there.
+fn find_start(text: &str, term: &str, anchor: Anchor) -> Option<usize> {
+    match anchor {
+        Anchor::None => text.find(term),
+        Anchor::Start => text
+            .starts_with(term)
+            .then_some(0),
+        Anchor::End => text
+            .ends_with(term)
+            .then(|| text.len() - term.len()),
+        Anchor::Exact => (text == term).then_some(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sensitive_matching_compares_case() {
+        assert_eq!(match_prefix("Firefox", "Fire", CaseSensitivity::Sensitive), Some(0..4));
+        assert_eq!(match_prefix("Firefox", "fire", CaseSensitivity::Sensitive), None);
+    }
+
+    #[test]
+    fn insensitive_matching_ignores_case() {
+        assert_eq!(match_prefix("firefox", "FIRE", CaseSensitivity::Insensitive), Some(0..4));
+        assert_eq!(match_prefix("firefox", "fox", CaseSensitivity::Insensitive), None);
+    }
+
+    #[test]
+    fn smart_matching_is_sensitive_with_uppercase() {
+        assert_eq!(match_prefix("Firefox", "fire", CaseSensitivity::Smart), Some(0..4));
+        assert_eq!(match_prefix("firefox", "Fire", CaseSensitivity::Smart), None);
+        assert_eq!(match_prefix("Firefox", "Fire", CaseSensitivity::Smart), Some(0..4));
+    }
+
+    #[test]
+    fn case_is_folded_beyond_ascii() {
+        assert_eq!(match_prefix("ÉDITEUR", "édi", CaseSensitivity::Insensitive), Some(0..4));
+        assert_eq!(match_prefix("ΣΟΦΙΑ", "σοφ", CaseSensitivity::Insensitive), Some(0..6));
+    }
+
+    #[test]
+    fn ranges_cover_characters_that_lowercase_to_many() {
+        // 'İ' lowercases to 'i' followed by a combining dot.
+        assert_eq!(match_prefix("İstanbul", "i", CaseSensitivity::Insensitive), Some(0..2));
+        assert_eq!(match_prefix("İstanbul", "i\u{307}s", CaseSensitivity::Insensitive), Some(0..3));
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn terms_match_in_any_order() {
+        let query = Query::parse("fox fire");
+
+        assert_eq!(query.matches("firefox", CaseSensitivity::Sensitive), Some(vec![0..7]));
+        assert_eq!(query.matches("foxfire", CaseSensitivity::Sensitive), Some(vec![0..7]));
+        assert_eq!(query.matches("fire", CaseSensitivity::Sensitive), None);
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn terms_can_be_negated_and_anchored() {
+        let query = Query::parse("^fi !roller x$");
+
+        assert_eq!(query.matches("firefox", CaseSensitivity::Sensitive), Some(vec![0..2, 6..7]));
+        assert_eq!(query.matches("file-roller-x", CaseSensitivity::Sensitive), None);
+        assert_eq!(query.matches("xfirefox", CaseSensitivity::Sensitive), None);
+
+        let exact = Query::parse("^vim$");
+
+        assert_eq!(exact.matches("vim", CaseSensitivity::Sensitive), Some(vec![0..3]));
+        assert_eq!(exact.matches("gvim", CaseSensitivity::Sensitive), None);
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn smart_case_applies_to_each_term() {
+        let query = Query::parse("Fire fox");
+
+        assert_eq!(query.matches("FireFox", CaseSensitivity::Smart), Some(vec![0..7]));
+        assert_eq!(query.matches("firefox", CaseSensitivity::Smart), None);
+    }
+
+    #[test]
+    fn lone_operators_are_ignored() {
+        assert_eq!(Query::parse("! ^ $ ^$").terms(), []);
+        assert_eq!(Query::parse("").matches("vim", CaseSensitivity::Sensitive), Some(Vec::new()));
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn regexes_highlight_every_match() {
+        let regex = compile_regex("o.", CaseSensitivity::Sensitive).unwrap();
+
+        assert_eq!(match_regex("libreoffice-writer", &regex), Some(vec![5..7]));
+        assert_eq!(match_regex("foo boo", &regex), Some(vec![1..3, 5..7]));
+        assert_eq!(match_regex("vim", &regex), None);
+
+        let empty = compile_regex("x*", CaseSensitivity::Sensitive).unwrap();
+        assert_eq!(match_regex("vim", &empty), Some(Vec::new()));
+    }
+
+    #[test]
+    fn regexes_follow_the_case_sensitivity() {
+        let regex = compile_regex("^fire", CaseSensitivity::Smart).unwrap();
+        assert!(match_regex("Firefox", &regex).is_some());
+
+        let regex = compile_regex("^Fire", CaseSensitivity::Smart).unwrap();
+        assert!(match_regex("firefox", &regex).is_none());
+    }
+
+    #[test]
+    fn regex_errors_are_one_line() {
+        assert_eq!(
+            compile_regex("(fire", CaseSensitivity::Sensitive).unwrap_err(),
+            "unclosed group"
+        );
+    }
+
+    #[test]
+    fn empty_queries_match_everything() {
+        assert_eq!(match_prefix("vim", "", CaseSensitivity::Insensitive), Some(0..0));
+        assert_eq!(match_prefix("vim", "", CaseSensitivity::Sensitive), Some(0..0));
+    }
+}
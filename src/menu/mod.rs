@@ -0,0 +1,3 @@
+pub mod line_editor;
+pub mod matching;
+pub mod state;
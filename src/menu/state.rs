@@ -0,0 +1,1244 @@
+use std::collections::BTreeSet;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use zeroize::Zeroize;
+
+use super::line_editor::LineEditor;
+use super::matching::{Query, compile_regex, match_prefix, match_regex};
+use crate::config::keybindings::Action;
+use crate::config::types::{CaseSensitivity, Matcher};
+
+// Generations are unique among every state, so a frontend
+// given another state lays it out even if it was filtered
+// as many times as the last one.
+static GENERATIONS: AtomicU64 = AtomicU64::new(0);
+
+#[inline]
+fn next_generation() -> u64 {
+    GENERATIONS.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+/// Input for the menu, frontends translate their
+/// key and mouse events into these.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuInput<'i> {
+    /// A bound action, like the ones in `[keybindings]`.
+    Action(Action),
+    /// A character typed without command modifiers.
+    Char(char),
+    /// Text coming from the clipboard.
+    Paste(&'i str),
+    /// Places the input caret at a character index.
+    SetCaret { position: usize, extend: bool },
+    /// Selects a match by its index.
+    Select(usize),
+}
+
+/// What the frontend should do after handling an input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MenuOutcome {
+    Continue,
+    /// The menu was accepted, there is something to start.
+    Accept(Selection),
+    /// The menu was dismissed.
+    Cancel,
+    /// The text should be set as the clipboard text.
+    Copy(String),
+    /// The text was selected with the mouse, X11 frontends
+    /// set it as the primary selection.
+    PrimarySelection(String),
+    /// The clipboard text, or the primary selection if
+    /// `primary`, should be sent back as `MenuInput::Paste`.
+    Paste {
+        primary: bool,
+    },
+    /// The next mode should be shown, or the previous one
+    /// if not `forward`, wrapping around at the ends.
+    CycleMode {
+        forward: bool,
+    },
+    /// A mode should be shown, by its index.
+    ShowMode(usize),
+}
+
+/// How the input is read.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QueryMode {
+    /// The first word matches the candidates as a prefix, the
+    /// rest are arguments for it, like a shell command line.
+    #[default]
+    Command,
+    /// The whole input is a query, space separated terms have to
+    /// match in any order, with `!`, `^` and `$` like fzf.
+    Query,
+}
+
+/// What's drawn along a candidate, and whether it can be chosen.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CandidateDetails {
+    description: Option<String>,
+    icon: Option<PathBuf>,
+    urgent: bool,
+    nonselectable: bool,
+}
+
+impl CandidateDetails {
+    /// Drawn after the candidate, dimmed.
+    #[inline]
+    pub fn description(&self) -> Option<&str> {
+        self.description
+            .as_deref()
+    }
+
+    #[inline]
+    pub fn set_description(&mut self, description: Option<String>) {
+        self.description = description;
+    }
+
+    /// A BMP image drawn before the candidate.
+    #[inline]
+    pub fn icon(&self) -> Option<&Path> {
+        self.icon.as_deref()
+    }
+
+    #[inline]
+    pub fn set_icon(&mut self, icon: Option<PathBuf>) {
+        self.icon = icon;
+    }
+
+    /// Whether it's drawn with the urgent color.
+    #[inline]
+    pub const fn urgent(&self) -> bool {
+        self.urgent
+    }
+
+    #[inline]
+    pub const fn set_urgent(&mut self, urgent: bool) {
+        self.urgent = urgent;
+    }
+
+    /// Whether accepting it accepts what's typed instead.
+    #[inline]
+    pub const fn nonselectable(&self) -> bool {
+        self.nonselectable
+    }
+
+    #[inline]
+    pub const fn set_nonselectable(&mut self, nonselectable: bool) {
+        self.nonselectable = nonselectable;
+    }
+}
+
+/// The result of accepting the menu.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Selection {
+    entry: Option<String>,
+    candidate: Option<usize>,
+    marked: Vec<String>,
+    input: String,
+    args: Vec<String>,
+    in_terminal: bool,
+}
+
+impl Selection {
+    /// The selected match, if there was one.
+    #[inline]
+    pub fn entry(&self) -> Option<&str> {
+        self.entry
+            .as_deref()
+    }
+
+    /// The index of the selected match among the candidates,
+    /// in the order they were given to `MenuState::in_order`.
+    #[inline]
+    pub const fn candidate(&self) -> Option<usize> {
+        self.candidate
+    }
+
+    /// The entries marked in multi-select mode, in the order of the
+    /// candidates, the selected match isn't included unless marked.
+    #[inline]
+    pub fn marked(&self) -> &[String] {
+        &self.marked
+    }
+
+    /// The typed input, as it was typed.
+    #[inline]
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// The typed input split in arguments.
+    #[inline]
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// Whether it was asked to be started in a terminal.
+    #[inline]
+    pub const fn in_terminal(&self) -> bool {
+        self.in_terminal
+    }
+}
+
+// The input can be a password.
+impl Drop for Selection {
+    fn drop(&mut self) {
+        self.input.zeroize();
+    }
+}
+
+// A candidate matching the query and the
+// byte ranges of it that matched.
+struct Match {
+    candidate: usize,
+    highlights: Vec<Range<usize>>,
+}
+
+/// The state of the menu independent of how it's drawn, the
+/// input, the candidates matching its first argument and which
+/// of them is selected.
+///
+/// How many matches fit in a page depends on the frontend, which
+/// sets them with `set_page_lengths` whenever `generation` changes.
+pub struct MenuState {
+    editor: LineEditor,
+
+    candidates: Vec<String>,
+    details: Vec<CandidateDetails>,
+    matches: Vec<Match>,
+    selected: usize,
+    case_sensitivity: CaseSensitivity,
+    query_mode: QueryMode,
+    matcher: Matcher,
+    regex_error: Option<String>,
+
+    // Why what was accepted couldn't be
+    // started, until anything else happens.
+    launch_error: Option<String>,
+
+    // Indexes of the marked candidates, these are kept
+    // when the matches are filtered again.
+    multi_select: bool,
+    marked: BTreeSet<usize>,
+
+    page_lengths: Vec<usize>,
+    last_query: Option<String>,
+    generation: u64,
+
+    in_args: bool,
+}
+
+impl MenuState {
+    pub fn new(mut candidates: Vec<String>) -> Self {
+        candidates.sort_by_key(|candidate| candidate.to_lowercase());
+        Self::in_order(candidates)
+    }
+
+    /// Keeps the candidates in the order they are given,
+    /// like dmenu does with the lines it reads.
+    pub fn in_order(candidates: Vec<String>) -> Self {
+        let mut state = Self {
+            editor: LineEditor::new(),
+
+            candidates,
+            details: Vec::new(),
+            matches: Vec::new(),
+            selected: 0,
+            case_sensitivity: CaseSensitivity::Sensitive,
+            query_mode: QueryMode::Command,
+            matcher: Matcher::Text,
+            regex_error: None,
+
+            launch_error: None,
+
+            multi_select: false,
+            marked: BTreeSet::new(),
+
+            page_lengths: Vec::new(),
+            last_query: None,
+            generation: 0,
+
+            in_args: false,
+        };
+
+        state.update_matches();
+        state
+    }
+
+    pub fn handle(&mut self, input: MenuInput) -> MenuOutcome {
+        self.launch_error = None;
+
+        let outcome = match input {
+            MenuInput::Action(action) => self.handle_action(action),
+
+            MenuInput::Char(letter) => {
+                self.editor
+                    .insert_char_at_caret(letter);
+                MenuOutcome::Continue
+            },
+
+            MenuInput::Paste(text) => {
+                self.editor
+                    .paste(text);
+                MenuOutcome::Continue
+            },
+
+            MenuInput::SetCaret { position, extend } => {
+                self.editor
+                    .set_caret(position, extend);
+                MenuOutcome::Continue
+            },
+
+            MenuInput::Select(index) => {
+                if index < self.matches.len() {
+                    self.selected = index;
+                }
+
+                MenuOutcome::Continue
+            },
+        };
+
+        self.update_matches();
+        outcome
+    }
+
+    fn handle_action(&mut self, action: Action) -> MenuOutcome {
+        let editor = &mut self.editor;
+
+        match action {
+            Action::Cancel => return MenuOutcome::Cancel,
+
+            Action::Accept => {
+                return self
+                    .accept()
+                    .map_or(MenuOutcome::Continue, MenuOutcome::Accept);
+            },
+
+            Action::AcceptInTerminal => {
+                return self
+                    .accept()
+                    .map_or(MenuOutcome::Continue, |mut selection| {
+                        selection.in_terminal = true;
+                        MenuOutcome::Accept(selection)
+                    });
+            },
+
+            Action::Complete => {
+                if !self.in_args
+                    && let Some(selected) = self.selected()
+                {
+                    let selected = selected.to_string();
+                    self.editor
+                        .set_text(&selected);
+                }
+            },
+
+            Action::ToggleMark => {
+                if self.multi_select
+                    && let Some(index) = self.selected_index()
+                {
+                    let candidate = self.matches[index].candidate;
+
+                    if !self
+                        .marked
+                        .remove(&candidate)
+                    {
+                        self.marked
+                            .insert(candidate);
+                    }
+
+                    self.select_next();
+                }
+            },
+
+            Action::Next => self.select_next(),
+            Action::Prev => self.select_previous(),
+            Action::PageNext => self.select_next_page(),
+            Action::PagePrev => self.select_previous_page(),
+
+            // The caret moves through the input and
+            // then continues through the matches.
+            Action::Left => {
+                if self.selected == 0 {
+                    editor.retreat_caret();
+                } else {
+                    self.select_previous();
+                }
+            },
+
+            Action::Right => {
+                if editor.is_caret_at_end() {
+                    self.select_next();
+                } else {
+                    editor.advance_caret();
+                }
+            },
+
+            Action::DeleteBackward => editor.remove_char_at_caret(),
+            Action::DeleteForward => editor.remove_char_after_caret(),
+            Action::WordLeft => editor.move_caret_word_left(),
+            Action::WordRight => editor.move_caret_word_right(),
+            Action::LineStart => editor.move_caret_to_start(),
+            Action::LineEnd => editor.move_caret_to_end(),
+
+            Action::DeleteWord => editor.remove_word_at_caret(),
+            Action::DeleteWordForward => editor.remove_word_after_caret(),
+            Action::KillLineStart => editor.kill_to_start(),
+            Action::KillLineEnd => editor.kill_to_end(),
+            Action::Yank => editor.yank(),
+            Action::YankPop => editor.yank_pop(),
+            Action::Undo => editor.undo(),
+            Action::Redo => editor.redo(),
+
+            Action::SelectLeft => editor.select_with(LineEditor::retreat_caret),
+            Action::SelectRight => editor.select_with(LineEditor::advance_caret),
+            Action::SelectWordLeft => editor.select_with(LineEditor::move_caret_word_left),
+            Action::SelectWordRight => editor.select_with(LineEditor::move_caret_word_right),
+            Action::SelectLineStart => editor.select_with(LineEditor::move_caret_to_start),
+            Action::SelectLineEnd => editor.select_with(LineEditor::move_caret_to_end),
+            Action::SelectAll => editor.select_all(),
+
+            Action::Copy => {
+                return editor
+                    .selected_text()
+                    .map_or(MenuOutcome::Continue, MenuOutcome::Copy);
+            },
+
+            Action::Paste => return MenuOutcome::Paste { primary: false },
+            Action::PastePrimary => return MenuOutcome::Paste { primary: true },
+
+            Action::NextMode => return MenuOutcome::CycleMode { forward: true },
+            Action::PrevMode => return MenuOutcome::CycleMode { forward: false },
+
+            Action::ToggleRegex => self.set_matcher(match self.matcher {
+                Matcher::Text => Matcher::Regex,
+                Matcher::Regex => Matcher::Text,
+            }),
+        }
+
+        MenuOutcome::Continue
+    }
+
+    // The selected match is started if nothing but its name was
+    // typed, otherwise what's typed, if there is anything.
+    fn accept(&self) -> Option<Selection> {
+        // Anything typed is accepted as a secret, even nothing.
+        if self.is_secret() {
+            return Some(Selection {
+                entry: None,
+                candidate: None,
+                marked: Vec::new(),
+                input: self.editor.text(),
+                args: Vec::new(),
+                in_terminal: false,
+            });
+        }
+
+        let args = self
+            .editor
+            .get_args();
+
+        // Nonselectable candidates are shown, but never accepted.
+        let candidate = self
+            .selected_index()
+            .map(|index| self.matches[index].candidate)
+            .filter(|candidate| {
+                !self
+                    .details
+                    .get(*candidate)
+                    .is_some_and(CandidateDetails::nonselectable)
+            });
+
+        let entry = candidate.map(|candidate| self.candidates[candidate].clone());
+
+        let marked = self
+            .marked
+            .iter()
+            .map(|candidate| self.candidates[*candidate].clone())
+            .collect::<Vec<_>>();
+
+        if entry.is_none()
+            && marked.is_empty()
+            && args
+                .first()
+                .is_none_or(|program| program.is_empty())
+        {
+            return None;
+        }
+
+        Some(Selection {
+            entry,
+            candidate,
+            marked,
+            input: self.editor.text(),
+            args,
+            in_terminal: false,
+        })
+    }
+
+    // Filters the candidates when the query changes,
+    // matching the same text again keeps the selection.
+    fn update_matches(&mut self) {
+        // Secrets aren't matched, so they aren't copied as a query.
+        if self.is_secret() {
+            if !self
+                .matches
+                .is_empty()
+            {
+                self.matches.clear();
+                self.page_lengths = vec![0];
+                self.generation = next_generation();
+            }
+
+            return;
+        }
+
+        let query = match self.query_mode {
+            QueryMode::Command => {
+                let args = self
+                    .editor
+                    .get_args();
+                self.in_args = args.len() > 1;
+
+                args.into_iter()
+                    .next()
+                    .unwrap_or_default()
+            },
+
+            QueryMode::Query => {
+                self.in_args = false;
+                self.editor.text()
+            },
+        };
+
+        if self
+            .last_query
+            .as_ref()
+            .is_some_and(|last_query| *last_query == query)
+        {
+            return;
+        }
+
+        let regex = match self.matcher {
+            Matcher::Text => None,
+            Matcher::Regex => match compile_regex(&query, self.case_sensitivity) {
+                Ok(regex) => Some(regex),
+
+                // The last matches stay while a regex is being written.
+                Err(err) => {
+                    self.regex_error = Some(err);
+                    self.last_query = Some(query);
+                    return;
+                },
+            },
+        };
+
+        self.regex_error = None;
+
+        let terms = match self.query_mode {
+            QueryMode::Command => None,
+            QueryMode::Query => Some(Query::parse(&query)),
+        };
+
+        self.matches = self
+            .candidates
+            .iter()
+            .enumerate()
+            .filter_map(|(candidate, text)| {
+                let highlights = match (&regex, &terms) {
+                    (Some(regex), _) => match_regex(text, regex)?,
+                    (None, Some(terms)) => terms.matches(text, self.case_sensitivity)?,
+                    (None, None) => {
+                        let highlight = match_prefix(text, &query, self.case_sensitivity)?;
+
+                        if highlight.is_empty() { Vec::new() } else { vec![highlight] }
+                    },
+                };
+
+                Some(Match { candidate, highlights })
+            })
+            .collect();
+
+        // Everything is a single page until the frontend lays it out.
+        self.page_lengths = vec![self.matches.len()];
+        self.selected = 0;
+        self.last_query = Some(query);
+        self.generation = next_generation();
+    }
+
+    fn select_next(&mut self) {
+        self.selected = (self.selected + 1).min(
+            self.matches
+                .len()
+                .saturating_sub(1),
+        );
+    }
+
+    fn select_previous(&mut self) {
+        self.selected = self
+            .selected
+            .saturating_sub(1);
+    }
+
+    fn select_next_page(&mut self) {
+        let next_page = self.page_of(self.selected) + 1;
+
+        if next_page < self.page_count() {
+            self.selected = self
+                .page_range(next_page)
+                .start;
+        }
+    }
+
+    fn select_previous_page(&mut self) {
+        self.selected = self
+            .page_range(
+                self.page_of(self.selected)
+                    .saturating_sub(1),
+            )
+            .start;
+    }
+
+    #[inline]
+    pub fn editor(&self) -> &LineEditor {
+        &self.editor
+    }
+
+    /// Whether more than a program name was typed,
+    /// in which case the matches aren't shown.
+    #[inline]
+    pub const fn in_args(&self) -> bool {
+        self.in_args
+    }
+
+    /// Changes every time the matches are filtered again, and
+    /// is never the same for two states.
+    #[inline]
+    pub const fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn matches(&self) -> impl ExactSizeIterator<Item = &str> {
+        self.matches
+            .iter()
+            .map(|found| self.candidates[found.candidate].as_str())
+    }
+
+    /// The byte ranges of a match the query matched.
+    pub fn highlights(&self, index: usize) -> &[Range<usize>] {
+        self.matches
+            .get(index)
+            .map_or(&[], |found| &found.highlights)
+    }
+
+    /// Sets what's drawn along each candidate, given its index in
+    /// the order they are kept and its text, `None` draws it plain.
+    pub fn set_details(
+        &mut self,
+        mut details: impl FnMut(usize, &str) -> Option<CandidateDetails>,
+    ) {
+        self.details = self
+            .candidates
+            .iter()
+            .enumerate()
+            .map(|(index, candidate)| details(index, candidate).unwrap_or_default())
+            .collect();
+        self.generation = next_generation();
+    }
+
+    /// What's drawn along a match, if anything.
+    pub fn details(&self, index: usize) -> Option<&CandidateDetails> {
+        self.matches
+            .get(index)
+            .and_then(|found| {
+                self.details
+                    .get(found.candidate)
+            })
+    }
+
+    /// Changes how case is compared, filtering again.
+    pub fn set_case_sensitivity(&mut self, case_sensitivity: CaseSensitivity) {
+        self.case_sensitivity = case_sensitivity;
+        self.last_query = None;
+        self.update_matches();
+    }
+
+    #[inline]
+    pub const fn query_mode(&self) -> QueryMode {
+        self.query_mode
+    }
+
+    /// Changes how the input is read, filtering again.
+    pub fn set_query_mode(&mut self, query_mode: QueryMode) {
+        self.query_mode = query_mode;
+        self.last_query = None;
+        self.update_matches();
+    }
+
+    #[inline]
+    pub const fn matcher(&self) -> Matcher {
+        self.matcher
+    }
+
+    /// Changes how the input filters the candidates, filtering again.
+    pub fn set_matcher(&mut self, matcher: Matcher) {
+        self.matcher = matcher;
+        self.last_query = None;
+        self.update_matches();
+    }
+
+    /// Why the input isn't a valid regex, the matches
+    /// are the ones of the last valid one meanwhile.
+    #[inline]
+    pub fn regex_error(&self) -> Option<&str> {
+        self.regex_error
+            .as_deref()
+    }
+
+    /// Shows why what was accepted couldn't be started,
+    /// until the next input is handled.
+    #[inline]
+    pub fn set_launch_error(&mut self, error: String) {
+        self.launch_error = Some(error);
+    }
+
+    #[inline]
+    pub fn launch_error(&self) -> Option<&str> {
+        self.launch_error
+            .as_deref()
+    }
+
+    /// Makes the input secret, see `LineEditor::set_secret`,
+    /// secret input is accepted as it is and never matched.
+    pub fn set_secret(&mut self, secret: bool) {
+        self.editor
+            .set_secret(secret);
+        self.last_query = None;
+        self.update_matches();
+    }
+
+    #[inline]
+    pub const fn is_secret(&self) -> bool {
+        self.editor
+            .is_secret()
+    }
+
+    /// Lets `Action::ToggleMark` mark several entries to accept.
+    #[inline]
+    pub const fn set_multi_select(&mut self, multi_select: bool) {
+        self.multi_select = multi_select;
+    }
+
+    /// Whether a match is marked.
+    pub fn is_marked(&self, index: usize) -> bool {
+        self.matches
+            .get(index)
+            .is_some_and(|found| {
+                self.marked
+                    .contains(&found.candidate)
+            })
+    }
+
+    /// The index of the selected match, if anything matches.
+    #[inline]
+    pub fn selected_index(&self) -> Option<usize> {
+        (self.selected < self.matches.len()).then_some(self.selected)
+    }
+
+    pub fn selected(&self) -> Option<&str> {
+        self.selected_index()
+            .map(|index| self.candidates[self.matches[index].candidate].as_str())
+    }
+
+    /// Splits the matches in pages of these lengths.
+    pub fn set_page_lengths(&mut self, page_lengths: Vec<usize>) {
+        self.page_lengths = page_lengths;
+    }
+
+    #[inline]
+    pub fn page_count(&self) -> usize {
+        self.page_lengths
+            .len()
+    }
+
+    /// The page a match is in.
+    pub fn page_of(&self, index: usize) -> usize {
+        let mut start = 0;
+
+        for (page, length) in self
+            .page_lengths
+            .iter()
+            .enumerate()
+        {
+            start += length;
+
+            if index < start {
+                return page;
+            }
+        }
+
+        self.page_count()
+            .saturating_sub(1)
+    }
+
+    /// The indexes of the matches in a page.
+    pub fn page_range(&self, page: usize) -> Range<usize> {
+        let start = self
+            .page_lengths
+            .iter()
+            .take(page)
+            .sum::<usize>();
+
+        start
+            ..start
+                + self
+                    .page_lengths
+                    .get(page)
+                    .copied()
+                    .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(candidates: &[&str]) -> MenuState {
+        MenuState::new(
+            candidates
+                .iter()
+                .map(|candidate| candidate.to_string())
+                .collect(),
+        )
+    }
+
+    fn type_text(state: &mut MenuState, text: &str) {
+        for letter in text.chars() {
+            state.handle(MenuInput::Char(letter));
+        }
+    }
+
+    fn action(state: &mut MenuState, action: Action) -> MenuOutcome {
+        state.handle(MenuInput::Action(action))
+    }
+
+    #[test]
+    fn candidates_are_sorted_ignoring_case() {
+        let state = state(&["vim", "Firefox", "bash"]);
+
+        assert_eq!(
+            state
+                .matches()
+                .collect::<Vec<_>>(),
+            ["bash", "Firefox", "vim"]
+        );
+    }
+
+    #[test]
+    fn typing_filters_by_prefix_and_resets_the_selection() {
+        let mut state = state(&["file-roller", "find", "firefox", "vim"]);
+
+        action(&mut state, Action::Next);
+        type_text(&mut state, "fi");
+
+        assert_eq!(
+            state
+                .matches()
+                .collect::<Vec<_>>(),
+            ["file-roller", "find", "firefox"]
+        );
+        assert_eq!(state.selected(), Some("file-roller"));
+
+        type_text(&mut state, "r");
+        assert_eq!(state.selected(), Some("firefox"));
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn case_sensitivity_applies_to_matches_and_highlights() {
+        let mut state = state(&["Firefox", "fish"]);
+        type_text(&mut state, "fi");
+
+        assert_eq!(
+            state
+                .matches()
+                .collect::<Vec<_>>(),
+            ["fish"]
+        );
+
+        state.set_case_sensitivity(CaseSensitivity::Smart);
+        assert_eq!(
+            state
+                .matches()
+                .collect::<Vec<_>>(),
+            ["Firefox", "fish"]
+        );
+        assert_eq!(state.highlights(0), [0..2]);
+
+        type_text(&mut state, "R");
+        assert_eq!(
+            state
+                .matches()
+                .len(),
+            0
+        );
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn query_mode_matches_every_term_and_keeps_the_order() {
+        let mut state = MenuState::in_order(
+            ["zathura notes.pdf", "vim notes.md", "vim todo.md"]
+                .iter()
+                .map(|candidate| candidate.to_string())
+                .collect(),
+        );
+        state.set_query_mode(QueryMode::Query);
+        type_text(&mut state, "notes !pdf");
+
+        assert!(!state.in_args());
+        assert_eq!(
+            state
+                .matches()
+                .collect::<Vec<_>>(),
+            ["vim notes.md"]
+        );
+        assert_eq!(state.highlights(0), [4..9]);
+
+        state.handle(MenuInput::Action(Action::DeleteWord));
+        assert_eq!(
+            state
+                .matches()
+                .collect::<Vec<_>>(),
+            ["zathura notes.pdf", "vim notes.md"]
+        );
+
+        let MenuOutcome::Accept(selection) = action(&mut state, Action::Accept) else {
+            panic!("the menu to be accepted");
+        };
+
+        assert_eq!(selection.entry(), Some("zathura notes.pdf"));
+        assert_eq!(selection.input(), "notes ");
+    }
+
+    #[test]
+    fn launch_errors_last_until_the_next_input() {
+        let mut state = state(&["firefox"]);
+        state.set_launch_error("'firefx' not found".to_string());
+
+        assert_eq!(state.launch_error(), Some("'firefx' not found"));
+
+        action(&mut state, Action::Left);
+
+        assert_eq!(state.launch_error(), None);
+    }
+
+    #[test]
+    fn details_follow_the_matches_and_nonselectable_ones_are_not_accepted() {
+        let mut state =
+            MenuState::in_order(vec!["Bookmarks".to_string(), "rust-lang.org".to_string()]);
+        state.set_query_mode(QueryMode::Query);
+        state.set_details(|index, _| {
+            let mut details = CandidateDetails::default();
+            details.set_nonselectable(index == 0);
+            details.set_urgent(index == 1);
+            Some(details)
+        });
+
+        // Nothing is typed and the selection can't be accepted.
+        assert_eq!(action(&mut state, Action::Accept), MenuOutcome::Continue);
+
+        type_text(&mut state, "rust");
+
+        assert!(
+            state
+                .details(0)
+                .is_some_and(CandidateDetails::urgent)
+        );
+
+        let MenuOutcome::Accept(selection) = action(&mut state, Action::Accept) else {
+            panic!("The menu to be accepted.");
+        };
+
+        assert_eq!(selection.entry(), Some("rust-lang.org"));
+        assert_eq!(selection.candidate(), Some(1));
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn invalid_regexes_keep_the_last_matches() {
+        let mut state = state(&["firefox", "fish", "vim"]);
+        action(&mut state, Action::ToggleRegex);
+        type_text(&mut state, "f(ire");
+
+        assert_eq!(state.matcher(), Matcher::Regex);
+        assert_eq!(state.regex_error(), Some("unclosed group"));
+        assert_eq!(
+            state
+                .matches()
+                .collect::<Vec<_>>(),
+            ["firefox", "fish"]
+        );
+
+        type_text(&mut state, ")|m$");
+
+        assert_eq!(state.regex_error(), None);
+        assert_eq!(
+            state
+                .matches()
+                .collect::<Vec<_>>(),
+            ["firefox", "vim"]
+        );
+        assert_eq!(state.highlights(1), [2..3]);
+
+        action(&mut state, Action::ToggleRegex);
+        assert_eq!(state.matcher(), Matcher::Text);
+        assert_eq!(
+            state
+                .matches()
+                .len(),
+            0
+        );
+    }
+
+    #[test]
+    fn marks_survive_filtering_and_are_accepted_in_order() {
+        let mut state = state(&["htop", "git", "vim", "gimp"]);
+
+        action(&mut state, Action::ToggleMark);
+        assert!(!state.is_marked(0));
+
+        state.set_multi_select(true);
+        type_text(&mut state, "gi");
+        action(&mut state, Action::Next);
+        action(&mut state, Action::ToggleMark);
+
+        assert!(state.is_marked(1));
+        assert_eq!(state.selected(), Some("git"));
+
+        state.handle(MenuInput::Action(Action::KillLineStart));
+        type_text(&mut state, "v");
+        action(&mut state, Action::ToggleMark);
+        state.handle(MenuInput::Action(Action::KillLineStart));
+
+        assert!(!state.is_marked(0));
+        assert!(state.is_marked(1));
+        assert!(state.is_marked(3));
+
+        let MenuOutcome::Accept(selection) = action(&mut state, Action::Accept) else {
+            panic!("the menu to be accepted");
+        };
+
+        assert_eq!(selection.marked(), ["git", "vim"]);
+    }
+
+    #[test]
+    fn secret_input_is_accepted_even_if_empty() {
+        let mut state = state(&["hunter2"]);
+        state.set_secret(true);
+        assert_eq!(
+            state
+                .matches()
+                .len(),
+            0
+        );
+
+        let MenuOutcome::Accept(selection) = action(&mut state, Action::Accept) else {
+            panic!("the menu to be accepted");
+        };
+        assert_eq!(selection.input(), "");
+
+        type_text(&mut state, "hunter2");
+        action(&mut state, Action::SelectAll);
+        assert_eq!(action(&mut state, Action::Copy), MenuOutcome::Continue);
+
+        let MenuOutcome::Accept(selection) = action(&mut state, Action::Accept) else {
+            panic!("the menu to be accepted");
+        };
+        assert_eq!(selection.entry(), None);
+        assert_eq!(selection.input(), "hunter2");
+    }
+
+    #[test]
+    fn next_and_prev_stop_at_the_ends() {
+        let mut state = state(&["a", "b"]);
+
+        action(&mut state, Action::Prev);
+        assert_eq!(state.selected(), Some("a"));
+
+        action(&mut state, Action::Next);
+        action(&mut state, Action::Next);
+        assert_eq!(state.selected(), Some("b"));
+    }
+
+    #[test]
+    fn nothing_is_selected_without_matches() {
+        let mut state = state(&["a"]);
+
+        type_text(&mut state, "z");
+        action(&mut state, Action::Next);
+
+        assert_eq!(state.selected(), None);
+        assert_eq!(state.selected_index(), None);
+    }
+
+    #[test]
+    fn pages_follow_the_frontend_layout() {
+        let mut state = state(&["a", "b", "c", "d", "e"]);
+        state.set_page_lengths(vec![2, 2, 1]);
+
+        action(&mut state, Action::PageNext);
+        assert_eq!(state.selected(), Some("c"));
+        assert_eq!(state.page_of(2), 1);
+
+        action(&mut state, Action::Next);
+        action(&mut state, Action::PageNext);
+        assert_eq!(state.selected(), Some("e"));
+
+        action(&mut state, Action::PageNext);
+        assert_eq!(state.selected(), Some("e"));
+
+        action(&mut state, Action::PagePrev);
+        assert_eq!(state.selected(), Some("c"));
+        assert_eq!(state.page_range(1), 2..4);
+
+        action(&mut state, Action::PagePrev);
+        action(&mut state, Action::PagePrev);
+        assert_eq!(state.selected(), Some("a"));
+    }
+
+    #[test]
+    fn filtering_invalidates_the_layout() {
+        let mut state = state(&["ab", "ac", "b"]);
+        state.set_page_lengths(vec![1, 1, 1]);
+
+        let generation = state.generation();
+        type_text(&mut state, "a");
+
+        assert_ne!(state.generation(), generation);
+        assert_eq!(state.page_count(), 1);
+        assert_eq!(state.page_range(0), 0..2);
+    }
+
+    #[test]
+    fn left_and_right_continue_from_the_input_to_the_matches() {
+        let mut state = state(&["ab", "ac"]);
+        type_text(&mut state, "a");
+
+        action(&mut state, Action::Right);
+        assert_eq!(state.selected(), Some("ac"));
+
+        action(&mut state, Action::Left);
+        assert_eq!(state.selected(), Some("ab"));
+        assert_eq!(
+            state
+                .editor()
+                .caret(),
+            1
+        );
+
+        action(&mut state, Action::Left);
+        assert_eq!(
+            state
+                .editor()
+                .caret(),
+            0
+        );
+
+        action(&mut state, Action::Right);
+        assert_eq!(
+            state
+                .editor()
+                .caret(),
+            1
+        );
+        assert_eq!(state.selected(), Some("ab"));
+    }
+
+    #[test]
+    fn complete_replaces_the_input_with_the_selection() {
+        let mut state = state(&["firefox", "fish"]);
+        type_text(&mut state, "fi");
+        action(&mut state, Action::Next);
+        action(&mut state, Action::Complete);
+
+        assert_eq!(
+            state
+                .editor()
+                .text(),
+            "fish"
+        );
+        assert_eq!(state.selected(), Some("fish"));
+    }
+
+    #[test]
+    fn arguments_hide_the_matches() {
+        let mut state = state(&["ls"]);
+        type_text(&mut state, "ls -la");
+
+        assert!(state.in_args());
+
+        action(&mut state, Action::Complete);
+        assert_eq!(
+            state
+                .editor()
+                .text(),
+            "ls -la"
+        );
+    }
+
+    #[test]
+    fn accept_returns_the_selection_and_arguments() {
+        let mut state = state(&["firefox"]);
+        type_text(&mut state, "fire");
+
+        let MenuOutcome::Accept(selection) = action(&mut state, Action::Accept) else {
+            panic!("the menu to be accepted");
+        };
+
+        assert_eq!(selection.entry(), Some("firefox"));
+        assert_eq!(selection.args(), ["fire"]);
+        assert!(!selection.in_terminal());
+
+        let MenuOutcome::Accept(selection) = action(&mut state, Action::AcceptInTerminal) else {
+            panic!("the menu to be accepted");
+        };
+
+        assert!(selection.in_terminal());
+    }
+
+    #[test]
+    fn accept_without_anything_to_start_continues() {
+        let mut state = state(&[]);
+
+        assert_eq!(action(&mut state, Action::Accept), MenuOutcome::Continue);
+    }
+
+    #[test]
+    fn clipboard_actions_are_left_to_the_frontend() {
+        let mut state = state(&[]);
+
+        assert_eq!(action(&mut state, Action::Paste), MenuOutcome::Paste { primary: false });
+        assert_eq!(action(&mut state, Action::Copy), MenuOutcome::Continue);
+
+        state.handle(MenuInput::Paste("echo\nhi"));
+        action(&mut state, Action::SelectAll);
+
+        assert_eq!(action(&mut state, Action::Copy), MenuOutcome::Copy("echo hi".to_string()));
+        assert_eq!(action(&mut state, Action::Cancel), MenuOutcome::Cancel);
+    }
+
+    #[test]
+    fn select_ignores_indexes_out_of_range() {
+        let mut state = state(&["a", "b"]);
+
+        state.handle(MenuInput::Select(1));
+        assert_eq!(state.selected(), Some("b"));
+
+        state.handle(MenuInput::Select(5));
+        assert_eq!(state.selected(), Some("b"));
+    }
+}
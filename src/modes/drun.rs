@@ -0,0 +1,265 @@
+use std::collections::HashSet;
+use std::fs::{read_dir, read_to_string};
+use std::process::Command;
+
+use log::info;
+
+use super::{Mode, ModeError};
+use crate::config::loader::Config;
+use crate::launch::launch;
+use crate::launch::terminal::{TerminalTemplate, applications_directories};
+use crate::menu::state::{CandidateDetails, MenuState, QueryMode, Selection};
+use crate::utils::shell_words::split_words;
+
+// An application from a desktop entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Application {
+    name: String,
+    comment: Option<String>,
+    exec: Vec<String>,
+    terminal: bool,
+}
+
+/// The applications of the desktop entries, listed by
+/// their name and started as the entry says.
+pub struct DrunMode<'c> {
+    config: &'c Config<'c>,
+
+    // In the order of the candidates.
+    applications: Vec<Application>,
+}
+
+impl<'c> DrunMode<'c> {
+    pub fn new(config: &'c Config<'c>) -> Self {
+        Self { config, applications: Vec::new() }
+    }
+}
+
+impl Mode for DrunMode<'_> {
+    fn name(&self) -> &str {
+        "drun"
+    }
+
+    fn start(&mut self) -> Result<MenuState, ModeError> {
+        self.applications = desktop_applications();
+
+        let mut state = MenuState::in_order(
+            self.applications
+                .iter()
+                .map(|application| {
+                    application
+                        .name
+                        .clone()
+                })
+                .collect(),
+        );
+        state.set_query_mode(QueryMode::Query);
+        state.set_details(|index, _| {
+            let mut details = CandidateDetails::default();
+            details.set_description(
+                self.applications[index]
+                    .comment
+                    .clone(),
+            );
+            Some(details)
+        });
+
+        Ok(state)
+    }
+
+    fn accept(&mut self, selection: &Selection) -> Result<Option<MenuState>, ModeError> {
+        let Some(application) = selection
+            .candidate()
+            .and_then(|candidate| {
+                self.applications
+                    .get(candidate)
+            })
+        else {
+            return Err(ModeError::NoMatch);
+        };
+
+        let mut command = Command::new(&application.exec[0]);
+        command.args(&application.exec[1..]);
+
+        if application.terminal || selection.in_terminal() {
+            command = self
+                .config
+                .terminal()
+                .cloned()
+                .unwrap_or_else(TerminalTemplate::fallback)
+                .wrap(&command);
+
+            info!("Starting it in a terminal, as {:?}", command.get_program());
+        }
+
+        info!("Requesting to start the application '{}'", application.name);
+
+        launch(
+            &mut command,
+            self.config
+                .launch_options(),
+        )?;
+
+        Ok(None)
+    }
+}
+
+// The applications in the XDG data directories sorted by name, an entry
+// in the data home hides the ones with the same file name in the rest.
+fn desktop_applications() -> Vec<Application> {
+    let mut seen = HashSet::new();
+    let mut applications = Vec::new();
+
+    for directory in applications_directories() {
+        let Ok(entries) = read_dir(&directory) else { continue };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+
+            if path
+                .extension()
+                .is_none_or(|extension| extension != "desktop")
+                || !seen.insert(entry.file_name())
+            {
+                continue;
+            }
+
+            if let Ok(desktop_entry) = read_to_string(&path)
+                && let Some(application) = parse_application(&desktop_entry)
+            {
+                applications.push(application);
+            }
+        }
+    }
+
+    applications.sort_by_key(|application| {
+        application
+            .name
+            .to_lowercase()
+    });
+
+    info!("Found {} desktop applications", applications.len());
+
+    applications
+}
+
+// The application a desktop entry describes, unless it's hidden.
+fn parse_application(desktop_entry: &str) -> Option<Application> {
+    let mut in_entry_group = false;
+    let mut name = None;
+    let mut comment = None;
+    let mut exec = None;
+    let mut terminal = false;
+    let mut application = false;
+    let mut hidden = false;
+
+    for line in desktop_entry.lines() {
+        let line = line.trim();
+
+        if line.starts_with('[') {
+            in_entry_group = line == "[Desktop Entry]";
+            continue;
+        }
+
+        if !in_entry_group {
+            continue;
+        }
+
+        match line
+            .split_once('=')
+            .map(|(key, value)| (key.trim(), value.trim()))
+        {
+            Some(("Name", value)) => name = Some(value),
+            Some(("Comment", value)) => comment = Some(value),
+            Some(("Exec", value)) => exec = Some(value),
+            Some(("Terminal", value)) => terminal = value == "true",
+            Some(("Type", value)) => application = value == "Application",
+            Some(("NoDisplay" | "Hidden", value)) => hidden |= value == "true",
+            _ => {},
+        }
+    }
+
+    if !application || hidden {
+        return None;
+    }
+
+    let name = name?;
+    let exec = exec_words(exec?, name)?;
+
+    Some(Application {
+        name: name.to_string(),
+        comment: comment
+            .filter(|comment| !comment.is_empty())
+            .map(str::to_string),
+        exec,
+        terminal,
+    })
+}
+
+// The words of an `Exec` key without its field codes, nothing is
+// opened with the application so the files and URLs are left out.
+fn exec_words(exec: &str, name: &str) -> Option<Vec<String>> {
+    let words = split_words(exec)
+        .ok()?
+        .into_iter()
+        .filter_map(|word| {
+            let mut expanded = String::with_capacity(word.len());
+            let mut chars = word.chars();
+
+            while let Some(character) = chars.next() {
+                if character != '%' {
+                    expanded.push(character);
+                    continue;
+                }
+
+                match chars.next() {
+                    Some('%') => expanded.push('%'),
+                    Some('c') => expanded.push_str(name),
+                    _ => {},
+                }
+            }
+
+            (!expanded.is_empty() || word.is_empty()).then_some(expanded)
+        })
+        .collect::<Vec<_>>();
+
+    (!words.is_empty()).then_some(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn desktop_entries_are_applications_without_field_codes() {
+        let application = parse_application(
+            "[Desktop Entry]\nType=Application\nName=Files\nName[es]=Archivos\nComment=Browse \
+             files\nExec=nautilus --new-window %U\n[Desktop Action new]\nExec=nautilus --other\n",
+        )
+        .unwrap();
+
+        assert_eq!(application.name, "Files");
+        assert_eq!(
+            application
+                .comment
+                .as_deref(),
+            Some("Browse files")
+        );
+        assert_eq!(application.exec, ["nautilus", "--new-window"]);
+        assert!(!application.terminal);
+
+        assert_eq!(
+            exec_words("sh -c 'echo 100%% %c' %f", "Echo"),
+            Some(vec!["sh".to_string(), "-c".to_string(), "echo 100% Echo".to_string()])
+        );
+    }
+
+    #[test]
+    fn hidden_entries_and_links_are_not_listed() {
+        assert!(
+            parse_application("[Desktop Entry]\nType=Application\nName=a\nExec=a\nNoDisplay=true")
+                .is_none()
+        );
+        assert!(parse_application("[Desktop Entry]\nType=Link\nName=a\nURL=https://a").is_none());
+    }
+}
@@ -0,0 +1,36 @@
+use super::{Mode, ModeError};
+use crate::menu::state::{MenuState, Selection};
+
+/// Entries given up front, such as the lines read with `--dmenu`,
+/// what's accepted closes the menu and is left to the caller.
+pub struct ListMode {
+    name: String,
+    state: Option<MenuState>,
+}
+
+impl ListMode {
+    pub fn new(name: &str, state: MenuState) -> Self {
+        Self {
+            name: name.to_string(),
+            state: Some(state),
+        }
+    }
+}
+
+impl Mode for ListMode {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    // Modes are only started once, the state is moved out.
+    fn start(&mut self) -> Result<MenuState, ModeError> {
+        Ok(self
+            .state
+            .take()
+            .unwrap_or_else(|| MenuState::in_order(Vec::new())))
+    }
+
+    fn accept(&mut self, _: &Selection) -> Result<Option<MenuState>, ModeError> {
+        Ok(None)
+    }
+}
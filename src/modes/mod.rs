@@ -0,0 +1,293 @@
+pub mod drun;
+pub mod list;
+pub mod run;
+pub mod script;
+pub mod window;
+
+use thiserror::Error;
+
+use self::drun::DrunMode;
+use self::run::RunMode;
+use self::script::{ScriptError, ScriptMode};
+use self::window::{WindowAction, WindowError, WindowMode};
+use crate::completions::path::PathError;
+use crate::config::loader::Config;
+use crate::launch::LaunchError;
+use crate::menu::state::{MenuState, Selection};
+
+#[derive(Error, Debug)]
+pub enum ModeError {
+    #[error("There is no mode or script named '{0}'")]
+    Unknown(String),
+
+    #[error("Nothing matches what's typed")]
+    NoMatch,
+
+    #[error("Couldn't list the programs: {0}")]
+    Path(#[from] PathError),
+
+    #[error("{0}")]
+    Launch(#[from] LaunchError),
+
+    #[error("{0}")]
+    Script(#[from] ScriptError),
+
+    #[error("{0}")]
+    Window(#[from] WindowError),
+}
+
+/// A source of entries shown in the menu and what's done with
+/// the accepted ones, the modes of a menu are switched between.
+pub trait Mode {
+    /// Shown in the mode bar.
+    fn name(&self) -> &str;
+
+    /// The entries shown when the mode is first shown.
+    fn start(&mut self) -> Result<MenuState, ModeError>;
+
+    /// Does something with what's accepted, the menu is closed unless
+    /// new entries are returned, to be shown instead, as a script does.
+    fn accept(&mut self, selection: &Selection) -> Result<Option<MenuState>, ModeError>;
+
+    /// The prompt the mode asks for, if any.
+    fn prompt(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// The modes named as in `--modes`, `run`, `drun`, `window`
+/// or one of the scripts.
+pub fn modes_by_name<'c>(
+    names: &[String],
+    config: &'c Config<'c>,
+    window_action: WindowAction,
+) -> Result<Vec<Box<dyn Mode + 'c>>, ModeError> {
+    names
+        .iter()
+        .map(|name| -> Result<Box<dyn Mode + 'c>, ModeError> {
+            match name.as_str() {
+                "run" => Ok(Box::new(RunMode::new(config))),
+                "drun" => Ok(Box::new(DrunMode::new(config))),
+                "window" => Ok(Box::new(WindowMode::new(window_action))),
+                name => config
+                    .scripts()
+                    .iter()
+                    .find(|script| script.name() == name)
+                    .map(|script| -> Box<dyn Mode + 'c> {
+                        Box::new(ScriptMode::new(script.clone()))
+                    })
+                    .ok_or_else(|| ModeError::Unknown(name.to_string())),
+            }
+        })
+        .collect()
+}
+
+/// The modes of a menu and the state of each one, so every mode keeps
+/// its own input, caret and history while others are shown.
+pub struct ModeSwitcher<'m> {
+    modes: Vec<Box<dyn Mode + 'm>>,
+
+    // Modes are only started the first time they are shown.
+    states: Vec<Option<MenuState>>,
+    active: usize,
+
+    // Sets how the states are matched, as they are started.
+    configure: Box<dyn Fn(&mut MenuState) + 'm>,
+}
+
+impl<'m> ModeSwitcher<'m> {
+    /// Starts the first mode, there must be at least one.
+    pub fn new(
+        modes: Vec<Box<dyn Mode + 'm>>,
+        configure: impl Fn(&mut MenuState) + 'm,
+    ) -> Result<Self, ModeError> {
+        let mut switcher = Self {
+            states: modes
+                .iter()
+                .map(|_| None)
+                .collect(),
+            modes,
+            active: 0,
+            configure: Box::new(configure),
+        };
+
+        switcher.show(0)?;
+        Ok(switcher)
+    }
+
+    pub fn names(&self) -> impl ExactSizeIterator<Item = &str> {
+        self.modes
+            .iter()
+            .map(|mode| mode.name())
+    }
+
+    /// The index of the mode shown.
+    #[inline]
+    pub const fn active(&self) -> usize {
+        self.active
+    }
+
+    pub fn prompt(&self) -> Option<&str> {
+        self.modes[self.active].prompt()
+    }
+
+    pub fn state(&self) -> &MenuState {
+        self.states[self.active]
+            .as_ref()
+            .expect("The active mode to be started.")
+    }
+
+    pub fn state_mut(&mut self) -> &mut MenuState {
+        self.states[self.active]
+            .as_mut()
+            .expect("The active mode to be started.")
+    }
+
+    /// Shows a mode, starting it if it wasn't, if it can't be
+    /// started the one shown stays.
+    pub fn show(&mut self, index: usize) -> Result<(), ModeError> {
+        if index >= self.modes.len() {
+            return Ok(());
+        }
+
+        if self.states[index].is_none() {
+            let mut state = self.modes[index].start()?;
+            (self.configure)(&mut state);
+            self.states[index] = Some(state);
+        }
+
+        self.active = index;
+        Ok(())
+    }
+
+    /// Shows the next mode, or the previous one, wrapping around.
+    pub fn cycle(&mut self, forward: bool) -> Result<(), ModeError> {
+        let count = self.modes.len();
+        let index =
+            if forward { (self.active + 1) % count } else { (self.active + count - 1) % count };
+
+        self.show(index)
+    }
+
+    /// Gives what's accepted to the mode shown, whether the menu
+    /// should close, if the mode lists new entries they are shown.
+    pub fn accept(&mut self, selection: &Selection) -> Result<bool, ModeError> {
+        match self.modes[self.active].accept(selection)? {
+            Some(mut state) => {
+                (self.configure)(&mut state);
+                self.states[self.active] = Some(state);
+                Ok(false)
+            },
+            None => Ok(true),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::list::ListMode;
+    use super::*;
+    use crate::config::keybindings::Action;
+    use crate::config::types::CaseSensitivity;
+    use crate::menu::state::{MenuInput, MenuOutcome};
+
+    // Lists its name, and what's accepted as a new entry.
+    struct Echo(&'static str);
+
+    impl Mode for Echo {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn start(&mut self) -> Result<MenuState, ModeError> {
+            Ok(MenuState::in_order(vec![self.0.to_string()]))
+        }
+
+        fn accept(&mut self, selection: &Selection) -> Result<Option<MenuState>, ModeError> {
+            Ok(Some(MenuState::in_order(vec![format!("{}!", selection.input())])))
+        }
+    }
+
+    fn switcher() -> ModeSwitcher<'static> {
+        ModeSwitcher::new(
+            vec![
+                Box::new(Echo("first")),
+                Box::new(Echo("second")),
+                Box::new(ListMode::new("list", MenuState::in_order(Vec::new()))),
+            ],
+            |state| state.set_case_sensitivity(CaseSensitivity::Insensitive),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn modes_keep_their_input_and_wrap_around() {
+        let mut switcher = switcher();
+        switcher
+            .state_mut()
+            .handle(MenuInput::Paste("FI"));
+
+        switcher
+            .cycle(false)
+            .unwrap();
+        assert_eq!(switcher.active(), 2);
+
+        switcher
+            .cycle(true)
+            .unwrap();
+        assert_eq!(switcher.active(), 0);
+        assert_eq!(
+            switcher
+                .state()
+                .editor()
+                .text(),
+            "FI"
+        );
+
+        // The input is matched as configured.
+        assert_eq!(
+            switcher
+                .state()
+                .matches()
+                .collect::<Vec<_>>(),
+            ["first"]
+        );
+    }
+
+    #[test]
+    fn accepted_entries_replace_the_state_or_close() {
+        let mut switcher = switcher();
+        switcher
+            .state_mut()
+            .handle(MenuInput::Paste("again"));
+
+        let MenuOutcome::Accept(selection) = switcher
+            .state_mut()
+            .handle(MenuInput::Action(Action::Accept))
+        else {
+            panic!("The menu to be accepted.");
+        };
+
+        assert!(
+            !switcher
+                .accept(&selection)
+                .unwrap()
+        );
+        assert_eq!(
+            switcher
+                .state()
+                .matches()
+                .collect::<Vec<_>>(),
+            ["again!"]
+        );
+
+        switcher
+            .show(2)
+            .unwrap();
+        assert!(
+            switcher
+                .accept(&selection)
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use log::info;
+
+use super::{Mode, ModeError};
+use crate::completions::entries::Entry;
+use crate::completions::path::get_path_programs;
+use crate::config::loader::Config;
+use crate::launch::terminal::TerminalTemplate;
+use crate::launch::{LaunchError, build_command, is_terminal_program, launch};
+use crate::menu::state::{MenuState, Selection};
+
+/// The programs in PATH and the configured entries, the input is
+/// a command line starting with one of them.
+pub struct RunMode<'c> {
+    config: &'c Config<'c>,
+}
+
+impl<'c> RunMode<'c> {
+    pub fn new(config: &'c Config<'c>) -> Self {
+        Self { config }
+    }
+}
+
+impl Mode for RunMode<'_> {
+    fn name(&self) -> &str {
+        "run"
+    }
+
+    fn start(&mut self) -> Result<MenuState, ModeError> {
+        let mut programs = get_path_programs()?;
+
+        // Entries take the place of the programs named the same.
+        programs.extend(
+            self.config
+                .entries()
+                .iter()
+                .map(|entry| {
+                    entry
+                        .name()
+                        .to_string()
+                }),
+        );
+
+        let mut state = MenuState::new(
+            programs
+                .into_iter()
+                .collect(),
+        );
+
+        state.set_details(|_, program| {
+            self.config
+                .entries()
+                .iter()
+                .find(|entry| entry.name() == program)
+                .map(Entry::details)
+        });
+
+        Ok(state)
+    }
+
+    // Starts the selected entry or what's typed, in a terminal
+    // if asked to or if it's a terminal program.
+    fn accept(&mut self, selection: &Selection) -> Result<Option<MenuState>, ModeError> {
+        let config = self.config;
+
+        let Some(mut command) =
+            build_command(selection.entry(), selection.input(), config.entries())
+                .map_err(LaunchError::from)?
+        else {
+            return Ok(None);
+        };
+
+        if selection.in_terminal()
+            || is_terminal_program(command.get_program(), config.terminal_programs())
+        {
+            command = config
+                .terminal()
+                .cloned()
+                .unwrap_or_else(TerminalTemplate::fallback)
+                .wrap(&command);
+
+            info!("Starting it in a terminal, as {:?}", command.get_program());
+        }
+
+        launch(&mut command, config.launch_options())?;
+
+        info!("Started gracefully... Have a jolly good day!");
+
+        Ok(None)
+    }
+}
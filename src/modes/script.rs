@@ -0,0 +1,805 @@
+use std::io::Error as IoError;
+use std::ops::{Deref, RangeInclusive};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+
+use log::{debug, info};
+use serde_json::Value as JsonValue;
+use thiserror::Error;
+use toml_edit::Item as TomlItem;
+
+use super::{Mode, ModeError};
+use crate::config::types::ConfigValueError;
+use crate::menu::state::{CandidateDetails, MenuState, QueryMode, Selection};
+use crate::utils::shell_words::split_words;
+
+// Separates the options of a row, and the option names from their values.
+const OPTION_SEPARATOR: char = '\x1f';
+
+#[derive(Error, Debug)]
+pub enum ScriptError {
+    #[error("Couldn't start the script '{script}': {source}")]
+    Start { script: String, source: IoError },
+
+    #[error(
+        "The script '{script}' failed, {status}{output}",
+        output = .output.as_ref().map(|output| format!(": {output}")).unwrap_or_default()
+    )]
+    Exited {
+        script: String,
+        status: ExitStatus,
+        output: Option<String>,
+    },
+
+    #[error("Line {line} of the script output {message}")]
+    Output { line: usize, message: String },
+
+    #[error("The script '{0}' only accepts its own entries")]
+    NoCustom(String),
+}
+
+/// How a script writes its entries.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScriptFormat {
+    /// A line per entry, with options after a NUL, as rofi scripts do.
+    #[default]
+    Rofi,
+    /// A JSON object per line, options are still written as rofi does.
+    Json,
+}
+
+/// A script defined in the configuration, shown as a menu of its own.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Script {
+    name: String,
+    command: Vec<String>,
+    format: ScriptFormat,
+}
+
+/// The `[[scripts]]` of the configuration.
+#[derive(Clone, Debug, Default)]
+pub struct ConfigScripts(Vec<Script>);
+
+// An entry written by a script.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct ScriptRow {
+    // What's passed back to the script when it's chosen,
+    // and what's shown instead, if it's not the same.
+    text: String,
+    display: Option<String>,
+
+    // Passed back as `ROFI_INFO`.
+    info: Option<String>,
+
+    description: Option<String>,
+    icon: Option<String>,
+    urgent: bool,
+    nonselectable: bool,
+}
+
+// Everything a script wrote in a run.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct ScriptListing {
+    rows: Vec<ScriptRow>,
+    prompt: Option<String>,
+    data: Option<String>,
+    no_custom: bool,
+
+    // Separates the rows, kept for the next runs.
+    delimiter: char,
+}
+
+/// Runs a script to get the entries of the menu and again with the chosen
+/// one, which either lists new entries or nothing to close the menu, using
+/// the protocol of rofi's script modes so the same scripts work.
+///
+/// The script is given the chosen entry as its argument along `ROFI_RETV`,
+/// 0 for the first run, 1 when an entry is chosen and 2 when what's typed
+/// is, `ROFI_INFO` with the `info` of the entry and `ROFI_DATA` with the
+/// `data` of the last run.
+pub struct ScriptMode {
+    script: Script,
+    listing: ScriptListing,
+}
+
+impl Script {
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The program and its arguments.
+    #[inline]
+    pub fn command(&self) -> &[String] {
+        &self.command
+    }
+
+    #[inline]
+    pub const fn format(&self) -> ScriptFormat {
+        self.format
+    }
+}
+
+impl TryFrom<TomlItem> for ScriptFormat {
+    type Error = ConfigValueError;
+
+    fn try_from(value: TomlItem) -> Result<Self, Self::Error> {
+        match value
+            .as_str()
+            .map(str::to_lowercase)
+            .as_deref()
+        {
+            Some("rofi") => Ok(Self::Rofi),
+            Some("json") => Ok(Self::Json),
+            Some(_) => Err(ConfigValueError::InvalidValue { possible: vec!["rofi", "json"] }),
+            None => Err(ConfigValueError::InvalidType { possible_types: vec!["String"] }),
+        }
+    }
+}
+
+impl TryFrom<TomlItem> for Script {
+    type Error = ConfigValueError;
+
+    fn try_from(value: TomlItem) -> Result<Self, Self::Error> {
+        let Some(table) = value.as_table_like() else {
+            return Err(ConfigValueError::InvalidType { possible_types: vec!["Table"] });
+        };
+
+        let string = |key: &str| {
+            table
+                .get(key)
+                .map(|value| {
+                    value
+                        .as_str()
+                        .ok_or(ConfigValueError::InvalidType { possible_types: vec!["String"] })
+                })
+                .transpose()
+        };
+
+        let name = string("name")?.ok_or_else(|| ConfigValueError::Entry {
+            entry: value
+                .to_string()
+                .trim()
+                .to_string(),
+            message: "has no name".to_string(),
+        })?;
+
+        let entry_err =
+            |message: String| ConfigValueError::Entry { entry: name.to_string(), message };
+
+        let command = split_words(string("command")?.unwrap_or_default())
+            .map_err(|err| entry_err(err.to_string()))?;
+
+        if command.is_empty() {
+            return Err(entry_err("has no command".to_string()));
+        }
+
+        let format = table
+            .get("format")
+            .map(|format| ScriptFormat::try_from(format.clone()))
+            .transpose()
+            .map_err(|err| entry_err(format!("has an invalid format, {err}")))?
+            .unwrap_or_default();
+
+        Ok(Self { name: name.to_string(), command, format })
+    }
+}
+
+impl TryFrom<TomlItem> for ConfigScripts {
+    type Error = ConfigValueError;
+
+    fn try_from(value: TomlItem) -> Result<Self, Self::Error> {
+        // `[[scripts]]` tables or an array of inline ones.
+        if let Some(tables) = value.as_array_of_tables() {
+            return tables
+                .iter()
+                .map(|table| Script::try_from(TomlItem::Table(table.clone())))
+                .collect::<Result<Vec<_>, _>>()
+                .map(Self);
+        }
+
+        if let Some(array) = value.as_array() {
+            return array
+                .iter()
+                .map(|table| Script::try_from(TomlItem::Value(table.clone())))
+                .collect::<Result<Vec<_>, _>>()
+                .map(Self);
+        }
+
+        Err(ConfigValueError::InvalidType { possible_types: vec!["Vec<Table>"] })
+    }
+}
+
+impl Deref for ConfigScripts {
+    type Target = [Script];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl ScriptRow {
+    // The icon is only drawn if it's a BMP image, rofi
+    // scripts usually give icon theme names instead.
+    fn details(&self) -> CandidateDetails {
+        let mut details = CandidateDetails::default();
+        details.set_description(
+            self.description
+                .clone(),
+        );
+        details.set_icon(
+            self.icon
+                .as_deref()
+                .map(Path::new)
+                .filter(|icon| {
+                    icon.extension()
+                        .is_some_and(|extension| extension.eq_ignore_ascii_case("bmp"))
+                        && icon.is_file()
+                })
+                .map(PathBuf::from),
+        );
+        details.set_urgent(self.urgent);
+        details.set_nonselectable(self.nonselectable);
+        details
+    }
+
+    // The options after the NUL of a row, alternating names and values.
+    fn set_options(&mut self, options: &str) {
+        let mut options = options.split(OPTION_SEPARATOR);
+
+        while let (Some(name), Some(value)) = (options.next(), options.next()) {
+            match name {
+                "display" => self.display = Some(value.to_string()),
+                "info" => self.info = Some(value.to_string()),
+                "icon" => self.icon = Some(value.to_string()),
+                "urgent" => self.urgent = is_true(value),
+                "nonselectable" => self.nonselectable = is_true(value),
+
+                // Like `meta` and `active`, nothing else means anything here.
+                _ => debug!("Ignoring the row option '{name}'"),
+            }
+        }
+    }
+
+    // A JSON object with the `text` of the row and any of
+    // `description`, `icon`, `data`, `urgent` and `nonselectable`.
+    fn from_json(line: &str) -> Result<Self, String> {
+        let value = serde_json::from_str::<JsonValue>(line).map_err(|err| err.to_string())?;
+
+        let Some(object) = value.as_object() else {
+            return Err("isn't a JSON object".to_string());
+        };
+
+        let string = |key: &str| match object.get(key) {
+            None | Some(JsonValue::Null) => Ok(None),
+            Some(JsonValue::String(value)) => Ok(Some(value.clone())),
+            Some(_) => Err(format!("has a '{key}' that isn't a string")),
+        };
+
+        let boolean = |key: &str| match object.get(key) {
+            None | Some(JsonValue::Null) => Ok(false),
+            Some(JsonValue::Bool(value)) => Ok(*value),
+            Some(_) => Err(format!("has a '{key}' that isn't a boolean")),
+        };
+
+        Ok(Self {
+            text: string("text")?.ok_or("has no 'text'")?,
+            display: None,
+
+            // Anything can be passed back, it's given as JSON if it's not a string.
+            info: match object.get("data") {
+                None | Some(JsonValue::Null) => None,
+                Some(JsonValue::String(data)) => Some(data.clone()),
+                Some(data) => Some(data.to_string()),
+            },
+
+            description: string("description")?,
+            icon: string("icon")?,
+            urgent: boolean("urgent")?,
+            nonselectable: boolean("nonselectable")?,
+        })
+    }
+}
+
+impl ScriptListing {
+    // Reads the output of a script, the rows end with the delimiter, which
+    // the `delim` option changes, and the options start with a NUL and
+    // always end with a newline.
+    fn parse(output: &str, format: ScriptFormat, delimiter: char) -> Result<Self, ScriptError> {
+        let mut listing = Self { delimiter, ..Self::default() };
+        let mut urgent_rows = Vec::new();
+        let mut rest = output;
+        let mut line = 0;
+
+        while !rest.is_empty() {
+            let end = if rest.starts_with('\0') { '\n' } else { listing.delimiter };
+            let (record, next) = rest
+                .split_once(end)
+                .unwrap_or((rest, ""));
+
+            rest = next;
+            line += 1;
+
+            if let Some(option) = record.strip_prefix('\0') {
+                let (name, value) = option
+                    .split_once(OPTION_SEPARATOR)
+                    .unwrap_or((option, ""));
+
+                match name {
+                    "prompt" => listing.prompt = Some(value.to_string()),
+                    "data" => listing.data = Some(value.to_string()),
+                    "no-custom" => listing.no_custom = is_true(value),
+                    "urgent" => urgent_rows = parse_rows(value),
+                    "delim" => {
+                        if let Some(delimiter) = unescape_delimiter(value) {
+                            listing.delimiter = delimiter;
+                        }
+                    },
+
+                    _ => debug!("Ignoring the script option '{name}'"),
+                }
+
+                continue;
+            }
+
+            if record
+                .trim()
+                .is_empty()
+            {
+                continue;
+            }
+
+            let row = match format {
+                ScriptFormat::Rofi => {
+                    let (text, options) = record
+                        .split_once('\0')
+                        .unwrap_or((record, ""));
+
+                    let mut row = ScriptRow {
+                        text: text.to_string(),
+                        ..ScriptRow::default()
+                    };
+                    row.set_options(options);
+                    row
+                },
+
+                ScriptFormat::Json => ScriptRow::from_json(record)
+                    .map_err(|message| ScriptError::Output { line, message })?,
+            };
+
+            listing
+                .rows
+                .push(row);
+        }
+
+        for (index, row) in listing
+            .rows
+            .iter_mut()
+            .enumerate()
+        {
+            row.urgent |= urgent_rows
+                .iter()
+                .any(|rows| rows.contains(&index));
+        }
+
+        Ok(listing)
+    }
+}
+
+impl ScriptMode {
+    pub fn new(script: Script) -> Self {
+        Self {
+            script,
+            listing: ScriptListing {
+                delimiter: '\n',
+                ..ScriptListing::default()
+            },
+        }
+    }
+
+    #[inline]
+    pub fn script(&self) -> &Script {
+        &self.script
+    }
+
+    // Runs the script and reads what it lists.
+    fn run(
+        &mut self,
+        retv: u8,
+        argument: Option<&str>,
+        info: Option<&str>,
+    ) -> Result<(), ScriptError> {
+        let mut command = Command::new(&self.script.command[0]);
+        command
+            .args(&self.script.command[1..])
+            .args(argument)
+            .env("ROFI_RETV", retv.to_string())
+            .stdin(Stdio::null());
+
+        match info {
+            Some(info) => command.env("ROFI_INFO", info),
+            None => command.env_remove("ROFI_INFO"),
+        };
+
+        match &self.listing.data {
+            Some(data) => command.env("ROFI_DATA", data),
+            None => command.env_remove("ROFI_DATA"),
+        };
+
+        info!("Running the script '{}' with ROFI_RETV={retv}", self.script.name);
+
+        let output = command
+            .output()
+            .map_err(|source| ScriptError::Start {
+                script: self
+                    .script
+                    .name
+                    .clone(),
+                source,
+            })?;
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        if !output
+            .status
+            .success()
+        {
+            return Err(ScriptError::Exited {
+                script: self
+                    .script
+                    .name
+                    .clone(),
+                status: output.status,
+                output: stderr
+                    .lines()
+                    .map(str::trim)
+                    .rfind(|line| !line.is_empty())
+                    .map(str::to_string),
+            });
+        }
+
+        if !stderr
+            .trim()
+            .is_empty()
+        {
+            debug!("The script '{}' wrote: {}", self.script.name, stderr.trim());
+        }
+
+        self.listing = ScriptListing::parse(
+            &String::from_utf8_lossy(&output.stdout),
+            self.script.format,
+            self.listing
+                .delimiter,
+        )?;
+
+        Ok(())
+    }
+
+    // The rows listed by the last run, showing
+    // their `display` text if they have one.
+    fn menu_state(&self) -> MenuState {
+        let rows = &self.listing.rows;
+
+        let mut state = MenuState::in_order(
+            rows.iter()
+                .map(|row| {
+                    row.display
+                        .clone()
+                        .unwrap_or_else(|| row.text.clone())
+                })
+                .collect(),
+        );
+        state.set_query_mode(QueryMode::Query);
+        state.set_details(|index, _| Some(rows[index].details()));
+        state
+    }
+}
+
+impl Mode for ScriptMode {
+    fn name(&self) -> &str {
+        &self.script.name
+    }
+
+    // The one asked for in the last run.
+    fn prompt(&self) -> Option<&str> {
+        self.listing
+            .prompt
+            .as_deref()
+    }
+
+    // Runs the script for the first time, the entries it lists
+    // are matched as a query, in the order they were written.
+    fn start(&mut self) -> Result<MenuState, ModeError> {
+        self.run(0, None, None)?;
+
+        Ok(self.menu_state())
+    }
+
+    // Runs the script with the chosen entry, or what's typed if
+    // none was, the menu is closed if it doesn't list anything.
+    fn accept(&mut self, selection: &Selection) -> Result<Option<MenuState>, ModeError> {
+        let row = selection
+            .candidate()
+            .and_then(|candidate| {
+                self.listing
+                    .rows
+                    .get(candidate)
+            })
+            .cloned();
+
+        match row {
+            Some(row) => self.run(1, Some(&row.text), row.info.as_deref())?,
+
+            None if self
+                .listing
+                .no_custom =>
+            {
+                return Err(ScriptError::NoCustom(
+                    self.script
+                        .name
+                        .clone(),
+                )
+                .into());
+            },
+
+            None => self.run(2, Some(selection.input()), None)?,
+        }
+
+        Ok((!self
+            .listing
+            .rows
+            .is_empty())
+        .then(|| self.menu_state()))
+    }
+}
+
+// Rofi reads any of these as true.
+fn is_true(value: &str) -> bool {
+    matches!(
+        value
+            .to_lowercase()
+            .as_str(),
+        "true" | "yes" | "1"
+    )
+}
+
+// A list of row indexes and ranges, such as `0,2-4`, as rofi reads them,
+// rows are checked against the ranges as these can be arbitrarily large.
+fn parse_rows(value: &str) -> Vec<RangeInclusive<usize>> {
+    value
+        .split(',')
+        .filter_map(|part| {
+            let part = part.trim();
+
+            let (start, end) = part
+                .split_once('-')
+                .unwrap_or((part, part));
+
+            Some(start.parse().ok()?..=end.parse().ok()?)
+        })
+        .collect()
+}
+
+// The `delim` option is usually written escaped, as in `\n`.
+fn unescape_delimiter(value: &str) -> Option<char> {
+    match value {
+        "\\n" => Some('\n'),
+        "\\t" => Some('\t'),
+        "\\0" => Some('\0'),
+        "\\x1f" => Some(OPTION_SEPARATOR),
+        value => value.chars().next(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::keybindings::Action;
+    use crate::menu::state::{MenuInput, MenuOutcome};
+
+    // Lists two entries, the first one opens a nested menu
+    // and anything typed fails, writing it to stderr.
+    #[cfg(unix)]
+    const SCRIPT: &str = r#"
+case "$ROFI_RETV" in
+    0) printf '\000prompt\037Pick\none\000info\0371\ntwo\n' ;;
+    1) if [ "$1" = one ] && [ "$ROFI_INFO" = 1 ]; then echo nested; fi ;;
+    2) echo "no $1" >&2; exit 3 ;;
+esac
+"#;
+
+    fn parse(output: &str, format: ScriptFormat) -> ScriptListing {
+        ScriptListing::parse(output, format, '\n').unwrap()
+    }
+
+    fn accept(
+        mode: &mut ScriptMode,
+        state: &mut MenuState,
+    ) -> Result<Option<MenuState>, ModeError> {
+        let MenuOutcome::Accept(selection) = state.handle(MenuInput::Action(Action::Accept)) else {
+            panic!("The menu to be accepted.");
+        };
+
+        mode.accept(&selection)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn scripts_run_again_with_the_chosen_entry() {
+        let mut mode = ScriptMode::new(Script {
+            name: "test".to_string(),
+            command: ["sh", "-c", SCRIPT, "sh"]
+                .map(str::to_string)
+                .to_vec(),
+            format: ScriptFormat::Rofi,
+        });
+
+        let mut state = mode
+            .start()
+            .unwrap();
+        assert_eq!(mode.prompt(), Some("Pick"));
+        assert_eq!(
+            state
+                .matches()
+                .collect::<Vec<_>>(),
+            ["one", "two"]
+        );
+
+        let mut nested = accept(&mut mode, &mut state)
+            .unwrap()
+            .expect("The script to list nested entries.");
+        assert_eq!(
+            nested
+                .matches()
+                .collect::<Vec<_>>(),
+            ["nested"]
+        );
+
+        // Nothing is listed after choosing a nested entry, so it closes.
+        assert!(
+            accept(&mut mode, &mut nested)
+                .unwrap()
+                .is_none()
+        );
+
+        let mut state = mode
+            .start()
+            .unwrap();
+        for letter in "three".chars() {
+            state.handle(MenuInput::Char(letter));
+        }
+
+        let Err(err) = accept(&mut mode, &mut state) else {
+            panic!("The script to fail.");
+        };
+
+        assert_eq!(err.to_string(), "The script 'test' failed, exit status: 3: no three");
+    }
+
+    #[test]
+    fn rofi_rows_have_options_after_a_nul() {
+        let listing = parse(
+            concat!(
+                "\0prompt\x1fSessions\n",
+                "\0data\x1fpage 2\n",
+                "\0urgent\x1f1-2\n",
+                "main\0info\x1f$1\x1fmeta\x1fx\n",
+                "work\0display\x1fwork (attached)\n",
+                "header\0nonselectable\x1ftrue\n",
+            ),
+            ScriptFormat::Rofi,
+        );
+
+        assert_eq!(
+            listing
+                .prompt
+                .as_deref(),
+            Some("Sessions")
+        );
+        assert_eq!(
+            listing
+                .data
+                .as_deref(),
+            Some("page 2")
+        );
+        assert_eq!(
+            listing
+                .rows
+                .iter()
+                .map(|row| (row.text.as_str(), row.urgent))
+                .collect::<Vec<_>>(),
+            [("main", false), ("work", true), ("header", true)]
+        );
+        assert_eq!(
+            listing.rows[0]
+                .info
+                .as_deref(),
+            Some("$1")
+        );
+        assert_eq!(
+            listing.rows[1]
+                .display
+                .as_deref(),
+            Some("work (attached)")
+        );
+        assert!(listing.rows[2].nonselectable);
+    }
+
+    #[test]
+    fn delimiters_change_for_the_next_rows() {
+        let listing =
+            parse("\0delim\x1f|\n\0no-custom\x1ftrue\nfirst\nline|second|", ScriptFormat::Rofi);
+
+        assert!(listing.no_custom);
+        assert_eq!(listing.delimiter, '|');
+        assert_eq!(
+            listing
+                .rows
+                .iter()
+                .map(|row| row.text.as_str())
+                .collect::<Vec<_>>(),
+            ["first\nline", "second"]
+        );
+    }
+
+    #[test]
+    fn json_rows_are_objects_with_a_text() {
+        let listing = parse(
+            concat!(
+                "\0prompt\x1fBookmarks\n",
+                r#"{"text": "Rust", "description": "rust-lang.org", "data": {"id": 1}}"#,
+                "\n\n",
+                r#"{"text": "Old", "urgent": true, "nonselectable": true, "data": "7"}"#,
+                "\n",
+            ),
+            ScriptFormat::Json,
+        );
+
+        assert_eq!(
+            listing
+                .prompt
+                .as_deref(),
+            Some("Bookmarks")
+        );
+        assert_eq!(
+            listing.rows[0]
+                .description
+                .as_deref(),
+            Some("rust-lang.org")
+        );
+        assert_eq!(
+            listing.rows[0]
+                .info
+                .as_deref(),
+            Some("{\"id\":1}")
+        );
+        assert!(listing.rows[1].urgent && listing.rows[1].nonselectable);
+        assert_eq!(
+            listing.rows[1]
+                .info
+                .as_deref(),
+            Some("7")
+        );
+
+        assert!(ScriptListing::parse("{\"text\": 1}\n", ScriptFormat::Json, '\n').is_err());
+        assert!(matches!(
+            ScriptListing::parse("{\"text\": \"a\"}\nnope\n", ScriptFormat::Json, '\n'),
+            Err(ScriptError::Output { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn urgent_ranges_can_go_past_the_rows() {
+        let listing =
+            parse("\0urgent\x1f1-18446744073709551615,nope\na\nb\nc\n", ScriptFormat::Rofi);
+
+        assert_eq!(
+            listing
+                .rows
+                .iter()
+                .map(|row| row.urgent)
+                .collect::<Vec<_>>(),
+            [false, true, true]
+        );
+    }
+}
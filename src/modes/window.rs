@@ -0,0 +1,474 @@
+use std::process::id as process_id;
+
+use log::info;
+use thiserror::Error;
+use x11rb::atom_manager;
+use x11rb::connection::Connection;
+use x11rb::errors::{ConnectError, ConnectionError, ReplyError};
+use x11rb::protocol::xproto::{
+    Atom,
+    AtomEnum,
+    ClientMessageEvent,
+    ConnectionExt,
+    EventMask,
+    GetPropertyReply,
+    Window,
+};
+use x11rb::rust_connection::RustConnection;
+
+use super::{Mode, ModeError};
+use crate::menu::state::{CandidateDetails, MenuState, QueryMode, Selection};
+
+// Requests sent as a pager would, which window managers
+// honour as the user asking for them directly.
+const SOURCE_PAGER: u32 = 2;
+const CURRENT_TIME: u32 = 0;
+
+// The desktop of the windows shown in all of them.
+const ALL_DESKTOPS: u32 = 0xFFFFFFFF;
+
+atom_manager! {
+    Atoms: AtomsCookie {
+        _NET_CLIENT_LIST,
+        _NET_ACTIVE_WINDOW,
+        _NET_CLOSE_WINDOW,
+        _NET_CURRENT_DESKTOP,
+        _NET_DESKTOP_NAMES,
+        _NET_WM_NAME,
+        _NET_WM_DESKTOP,
+        _NET_WM_PID,
+        UTF8_STRING,
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum WindowError {
+    #[error("Couldn't connect to the X server: {0}")]
+    Connect(#[from] ConnectError),
+
+    #[error("The connection to the X server failed: {0}")]
+    Connection(#[from] ConnectionError),
+
+    #[error("The X server refused a request: {0}")]
+    Reply(#[from] ReplyError),
+
+    #[error("The window manager doesn't list its windows in _NET_CLIENT_LIST")]
+    NoClientList,
+}
+
+/// What's done with the chosen window.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WindowAction {
+    #[default]
+    Activate,
+    Close,
+}
+
+/// A top-level window, as the window manager lists it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClientWindow {
+    id: Window,
+    name: String,
+    class: String,
+
+    // None if it's shown in all of them.
+    desktop: Option<u32>,
+}
+
+impl ClientWindow {
+    #[inline]
+    pub const fn id(&self) -> Window {
+        self.id
+    }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The second string of `WM_CLASS`, the application class.
+    #[inline]
+    pub fn class(&self) -> &str {
+        &self.class
+    }
+
+    /// The desktop index, `None` for windows shown in all of them.
+    #[inline]
+    pub const fn desktop(&self) -> Option<u32> {
+        self.desktop
+    }
+}
+
+/// The windows of an EWMH compliant window manager,
+/// listed and activated or closed through the root window.
+pub struct Ewmh {
+    connection: RustConnection,
+    root: Window,
+    atoms: Atoms,
+}
+
+impl Ewmh {
+    /// Connects to a display, or the one in `DISPLAY`.
+    pub fn connect(display: Option<&str>) -> Result<Self, WindowError> {
+        let (connection, screen) = x11rb::connect(display)?;
+        let root = connection
+            .setup()
+            .roots[screen]
+            .root;
+        let atoms = Atoms::new(&connection)?.reply()?;
+
+        Ok(Self { connection, root, atoms })
+    }
+
+    fn property(
+        &self,
+        window: Window,
+        property: impl Into<Atom>,
+        kind: impl Into<Atom>,
+    ) -> Result<GetPropertyReply, WindowError> {
+        Ok(self
+            .connection
+            .get_property(false, window, property, kind, 0, u32::MAX)?
+            .reply()?)
+    }
+
+    fn cardinal(&self, window: Window, property: Atom) -> Result<Option<u32>, WindowError> {
+        Ok(self
+            .property(window, property, AtomEnum::CARDINAL)?
+            .value32()
+            .and_then(|mut values| values.next()))
+    }
+
+    /// The windows in `_NET_CLIENT_LIST`, in the order they were
+    /// mapped, leaving out the ones of this process.
+    pub fn windows(&self) -> Result<Vec<ClientWindow>, WindowError> {
+        let clients = self
+            .property(
+                self.root,
+                self.atoms
+                    ._NET_CLIENT_LIST,
+                AtomEnum::WINDOW,
+            )?
+            .value32()
+            .ok_or(WindowError::NoClientList)?
+            .collect::<Vec<_>>();
+
+        let mut windows = Vec::with_capacity(clients.len());
+
+        for id in clients {
+            match self.client_window(id) {
+                Ok(Some(window)) => windows.push(window),
+                Ok(None) => {},
+
+                // Windows can be destroyed while they are listed.
+                Err(WindowError::Reply(ReplyError::X11Error(_))) => {},
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(windows)
+    }
+
+    fn client_window(&self, id: Window) -> Result<Option<ClientWindow>, WindowError> {
+        if self.cardinal(
+            id,
+            self.atoms
+                ._NET_WM_PID,
+        )? == Some(process_id())
+        {
+            return Ok(None);
+        }
+
+        let mut name = String::from_utf8_lossy(
+            &self
+                .property(
+                    id,
+                    self.atoms
+                        ._NET_WM_NAME,
+                    self.atoms
+                        .UTF8_STRING,
+                )?
+                .value,
+        )
+        .into_owned();
+
+        if name.is_empty() {
+            name = latin1(
+                &self
+                    .property(id, AtomEnum::WM_NAME, AtomEnum::STRING)?
+                    .value,
+            );
+        }
+
+        let class = null_separated(
+            &self
+                .property(id, AtomEnum::WM_CLASS, AtomEnum::STRING)?
+                .value,
+        )
+        .into_iter()
+        .nth(1)
+        .map_or_else(String::new, |class| latin1(class.as_bytes()));
+
+        let desktop = self
+            .cardinal(
+                id,
+                self.atoms
+                    ._NET_WM_DESKTOP,
+            )?
+            .filter(|desktop| *desktop != ALL_DESKTOPS);
+
+        Ok(Some(ClientWindow { id, name, class, desktop }))
+    }
+
+    /// The names of the desktops, by index, there may be
+    /// more desktops than names or none at all.
+    pub fn desktop_names(&self) -> Result<Vec<String>, WindowError> {
+        Ok(null_separated(
+            &self
+                .property(
+                    self.root,
+                    self.atoms
+                        ._NET_DESKTOP_NAMES,
+                    self.atoms
+                        .UTF8_STRING,
+                )?
+                .value,
+        ))
+    }
+
+    pub fn current_desktop(&self) -> Result<Option<u32>, WindowError> {
+        self.cardinal(
+            self.root,
+            self.atoms
+                ._NET_CURRENT_DESKTOP,
+        )
+    }
+
+    pub fn active_window(&self) -> Result<Option<Window>, WindowError> {
+        Ok(self
+            .property(
+                self.root,
+                self.atoms
+                    ._NET_ACTIVE_WINDOW,
+                AtomEnum::WINDOW,
+            )?
+            .value32()
+            .and_then(|mut values| values.next())
+            .filter(|window| *window != x11rb::NONE))
+    }
+
+    /// Asks the window manager to focus and raise a window,
+    /// switching to its desktop first if it's in another one.
+    pub fn activate(&self, window: &ClientWindow) -> Result<(), WindowError> {
+        if let Some(desktop) = window.desktop
+            && self.current_desktop()? != Some(desktop)
+        {
+            self.send_message(
+                self.root,
+                self.atoms
+                    ._NET_CURRENT_DESKTOP,
+                [desktop, CURRENT_TIME, 0, 0, 0],
+            )?;
+        }
+
+        let active = self
+            .active_window()?
+            .unwrap_or(x11rb::NONE);
+
+        self.send_message(
+            window.id,
+            self.atoms
+                ._NET_ACTIVE_WINDOW,
+            [SOURCE_PAGER, CURRENT_TIME, active, 0, 0],
+        )
+    }
+
+    /// Asks the window manager to close a window, as its
+    /// close button would.
+    pub fn close(&self, window: &ClientWindow) -> Result<(), WindowError> {
+        self.send_message(
+            window.id,
+            self.atoms
+                ._NET_CLOSE_WINDOW,
+            [CURRENT_TIME, SOURCE_PAGER, 0, 0, 0],
+        )
+    }
+
+    // Sends a client message to the root window, where
+    // the window manager reads the requests of others.
+    fn send_message(
+        &self,
+        window: Window,
+        message: Atom,
+        data: [u32; 5],
+    ) -> Result<(), WindowError> {
+        let event = ClientMessageEvent::new(32, window, message, data);
+
+        self.connection
+            .send_event(
+                false,
+                self.root,
+                EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+                event,
+            )?
+            .check()?;
+
+        Ok(())
+    }
+}
+
+// Properties in the STRING type are encoded as Latin-1,
+// where every byte is the code point of its character.
+fn latin1(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| *byte as char)
+        .collect()
+}
+
+// The strings of a list property, each one ends with a NUL.
+fn null_separated(bytes: &[u8]) -> Vec<String> {
+    let bytes = bytes
+        .strip_suffix(b"\0")
+        .unwrap_or(bytes);
+
+    if bytes.is_empty() {
+        return Vec::new();
+    }
+
+    bytes
+        .split(|byte| *byte == 0)
+        .map(|string| String::from_utf8_lossy(string).into_owned())
+        .collect()
+}
+
+// The class of a window and the desktop it's in, by its
+// name if it has one, drawn after the window name.
+fn window_description(window: &ClientWindow, desktop_names: &[String]) -> Option<String> {
+    let desktop = window
+        .desktop
+        .map(|desktop| {
+            desktop_names
+                .get(desktop as usize)
+                .filter(|name| !name.is_empty())
+                .cloned()
+                .unwrap_or_else(|| format!("desktop {}", desktop + 1))
+        });
+
+    match (
+        window
+            .class
+            .as_str(),
+        desktop,
+    ) {
+        ("", desktop) => desktop,
+        (class, None) => Some(class.to_string()),
+        (class, Some(desktop)) => Some(format!("{class}, {desktop}")),
+    }
+}
+
+/// The open windows, listed by their name and activated, or
+/// closed, through the window manager.
+pub struct WindowMode {
+    action: WindowAction,
+
+    // Connected when the mode is first shown.
+    ewmh: Option<Ewmh>,
+
+    // In the order of the candidates.
+    windows: Vec<ClientWindow>,
+}
+
+impl WindowMode {
+    pub fn new(action: WindowAction) -> Self {
+        Self { action, ewmh: None, windows: Vec::new() }
+    }
+}
+
+impl Mode for WindowMode {
+    fn name(&self) -> &str {
+        "window"
+    }
+
+    fn start(&mut self) -> Result<MenuState, ModeError> {
+        let ewmh = Ewmh::connect(None)?;
+        let desktop_names = ewmh.desktop_names()?;
+        self.windows = ewmh.windows()?;
+
+        info!("Found {} windows", self.windows.len());
+
+        let mut state = MenuState::in_order(
+            self.windows
+                .iter()
+                .map(|window| window.name.clone())
+                .collect(),
+        );
+        state.set_query_mode(QueryMode::Query);
+        state.set_details(|index, _| {
+            let mut details = CandidateDetails::default();
+            details.set_description(window_description(&self.windows[index], &desktop_names));
+            Some(details)
+        });
+
+        self.ewmh = Some(ewmh);
+        Ok(state)
+    }
+
+    fn accept(&mut self, selection: &Selection) -> Result<Option<MenuState>, ModeError> {
+        let (Some(ewmh), Some(window)) = (
+            &self.ewmh,
+            selection
+                .candidate()
+                .and_then(|candidate| {
+                    self.windows
+                        .get(candidate)
+                }),
+        ) else {
+            return Err(ModeError::NoMatch);
+        };
+
+        match self.action {
+            WindowAction::Activate => ewmh.activate(window)?,
+            WindowAction::Close => ewmh.close(window)?,
+        }
+
+        info!("Requested to {:?} the window '{}'", self.action, window.name);
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_properties_are_split_at_each_nul() {
+        assert_eq!(null_separated(b"xterm\0XTerm\0"), ["xterm", "XTerm"]);
+        assert_eq!(null_separated(b"web\0\0mail"), ["web", "", "mail"]);
+        assert!(null_separated(b"").is_empty());
+        assert_eq!(latin1(b"caf\xe9"), "café");
+    }
+
+    #[test]
+    fn descriptions_name_the_class_and_desktop() {
+        let mut window = ClientWindow {
+            id: 1,
+            name: "~".to_string(),
+            class: "XTerm".to_string(),
+            desktop: Some(1),
+        };
+        let names = ["main".to_string(), String::new()];
+
+        assert_eq!(window_description(&window, &names).as_deref(), Some("XTerm, desktop 2"));
+
+        window.desktop = Some(0);
+        assert_eq!(window_description(&window, &names).as_deref(), Some("XTerm, main"));
+
+        window.desktop = None;
+        assert_eq!(window_description(&window, &names).as_deref(), Some("XTerm"));
+
+        window.class.clear();
+        assert_eq!(window_description(&window, &names), None);
+    }
+}
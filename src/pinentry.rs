@@ -0,0 +1,327 @@
+use std::io::{BufRead, Error as IoError, Write};
+use std::process::id as process_id;
+
+use thiserror::Error;
+use zeroize::Zeroize;
+
+// The libgpg-error codes pinentry answers with.
+const CANCELLED: &str = "ERR 83886179 Operation cancelled <Pinentry>";
+const UNKNOWN_COMMAND: &str = "ERR 536871187 Unknown IPC command <User defined source 1>";
+const UNKNOWN_INFO: &str = "ERR 83886360 IPC parameter error <Pinentry>";
+
+#[derive(Error, Debug)]
+pub enum PinentryError {
+    #[error("A problem occurred while talking the Assuan protocol.")]
+    IoError(#[from] IoError),
+}
+
+/// Which dialog is requested.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PinDialog {
+    /// Asks for a PIN or passphrase.
+    GetPin,
+    /// Asks whether to continue.
+    Confirm,
+    /// Shows a message.
+    Message,
+}
+
+/// The texts set with the `SET*` commands before a dialog.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PinRequest {
+    title: String,
+    description: String,
+    prompt: String,
+    error: String,
+
+    ok: String,
+    cancel: String,
+}
+
+impl PinRequest {
+    #[inline]
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    #[inline]
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    #[inline]
+    pub fn prompt(&self) -> &str {
+        &self.prompt
+    }
+
+    /// Why the last PIN was wrong, if it was.
+    #[inline]
+    pub fn error(&self) -> &str {
+        &self.error
+    }
+
+    /// The label of the button accepting the dialog.
+    #[inline]
+    pub fn ok(&self) -> &str {
+        if self.ok.is_empty() { "OK" } else { &self.ok }
+    }
+
+    /// The label of the button dismissing the dialog.
+    #[inline]
+    pub fn cancel(&self) -> &str {
+        if self
+            .cancel
+            .is_empty()
+        {
+            "Cancel"
+        } else {
+            &self.cancel
+        }
+    }
+
+    /// The error, description and prompt in a single line.
+    pub fn label(&self) -> String {
+        [&self.error, &self.description, &self.prompt]
+            .into_iter()
+            .filter(|text| !text.is_empty())
+            .map(|text| {
+                text.split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+// Assuan escapes `%`, carriage returns and new lines as `%XX`.
+fn unescape(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        if bytes[index] == b'%'
+            && let Some(byte) = text
+                .get(index + 1..index + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            unescaped.push(byte);
+            index += 3;
+        } else {
+            unescaped.push(bytes[index]);
+            index += 1;
+        }
+    }
+
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Serves the pinentry Assuan protocol, as gpg-agent talks it through
+/// stdin and stdout, `ask` shows each dialog and returns the entered
+/// PIN, or anything for an accepted confirmation, unless dismissed.
+pub fn serve(
+    input: impl BufRead,
+    mut output: impl Write,
+    mut ask: impl FnMut(PinDialog, &PinRequest) -> Option<String>,
+) -> Result<(), PinentryError> {
+    let mut request = PinRequest::default();
+
+    writeln!(output, "OK Pleased to meet you")?;
+    output.flush()?;
+
+    for line in input.lines() {
+        let line = line?;
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (command, argument) = line
+            .split_once(' ')
+            .unwrap_or((&line, ""));
+        let argument = unescape(argument);
+
+        match command
+            .to_uppercase()
+            .as_str()
+        {
+            "SETTITLE" => request.title = argument,
+            "SETDESC" => request.description = argument,
+            "SETPROMPT" => request.prompt = argument,
+            "SETERROR" => request.error = argument,
+            "SETOK" => request.ok = argument,
+            "SETCANCEL" => request.cancel = argument,
+
+            "GETPIN" => {
+                let pin = ask(PinDialog::GetPin, &request);
+
+                // The error was for the PIN entered last time.
+                request
+                    .error
+                    .clear();
+
+                match pin {
+                    Some(mut pin) => {
+                        if !pin.is_empty() {
+                            let mut data = format!("D {}\n", escape(&pin));
+                            output.write_all(data.as_bytes())?;
+                            data.zeroize();
+                        }
+
+                        pin.zeroize();
+                    },
+
+                    None => {
+                        writeln!(output, "{CANCELLED}")?;
+                        output.flush()?;
+                        continue;
+                    },
+                }
+            },
+
+            "CONFIRM" | "MESSAGE" => {
+                let dialog = if command.eq_ignore_ascii_case("CONFIRM") {
+                    PinDialog::Confirm
+                } else {
+                    PinDialog::Message
+                };
+
+                let confirmed = ask(dialog, &request).is_some();
+
+                request
+                    .error
+                    .clear();
+
+                if !confirmed && dialog == PinDialog::Confirm {
+                    writeln!(output, "{CANCELLED}")?;
+                    output.flush()?;
+                    continue;
+                }
+            },
+
+            "GETINFO" => {
+                let info = match argument.as_str() {
+                    "pid" => process_id().to_string(),
+                    "version" => env!("CARGO_PKG_VERSION").to_string(),
+                    "flavor" => "rmenu".to_string(),
+                    "ttyinfo" => "- - -".to_string(),
+
+                    _ => {
+                        writeln!(output, "{UNKNOWN_INFO}")?;
+                        output.flush()?;
+                        continue;
+                    },
+                };
+
+                writeln!(output, "D {}", escape(&info))?;
+            },
+
+            "RESET" => request = PinRequest::default(),
+
+            "BYE" => {
+                writeln!(output, "OK closing connection")?;
+                output.flush()?;
+                return Ok(());
+            },
+
+            // Options and settings this frontend has no use for.
+            "OPTION" | "NOP" | "SETKEYINFO" | "SETREPEAT" | "SETREPEATERROR" | "SETQUALITYBAR"
+            | "SETQUALITYBAR_TT" | "SETGENPIN" | "SETGENPIN_TT" | "SETNOTOK" | "SETTIMEOUT"
+            | "CLEARPASSPHRASE" => {},
+
+            _ => {
+                writeln!(output, "{UNKNOWN_COMMAND}")?;
+                output.flush()?;
+                continue;
+            },
+        }
+
+        writeln!(output, "OK")?;
+        output.flush()?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn converse(
+        input: &str,
+        ask: impl FnMut(PinDialog, &PinRequest) -> Option<String>,
+    ) -> Vec<String> {
+        let mut output = Vec::new();
+        serve(input.as_bytes(), &mut output, ask).expect("The conversation to succeed.");
+
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn getpin_returns_the_escaped_pin() {
+        let mut labels = Vec::new();
+
+        let output = converse(
+            "SETDESC Unlock%0Athe key\nSETPROMPT PIN:\nGETPIN\nBYE\n",
+            |dialog, request| {
+                labels.push((dialog, request.label()));
+                Some("100%\nsure".to_string())
+            },
+        );
+
+        assert_eq!(labels, [(PinDialog::GetPin, "Unlock the key PIN:".to_string())]);
+        assert_eq!(
+            output,
+            [
+                "OK Pleased to meet you",
+                "OK",
+                "OK",
+                "D 100%25%0Asure",
+                "OK",
+                "OK closing connection"
+            ]
+        );
+    }
+
+    #[test]
+    fn dismissed_dialogs_are_cancelled() {
+        let output = converse("GETPIN\nCONFIRM\nMESSAGE\n", |_, _| None);
+
+        assert_eq!(output[1..], [CANCELLED, CANCELLED, "OK"]);
+    }
+
+    #[test]
+    fn errors_are_shown_once() {
+        let mut errors = Vec::new();
+
+        converse("SETERROR Bad PIN\nGETPIN\nGETPIN\n", |_, request| {
+            errors.push(
+                request
+                    .error()
+                    .to_string(),
+            );
+            Some(String::new())
+        });
+
+        assert_eq!(errors, ["Bad PIN", ""]);
+    }
+
+    #[test]
+    fn unknown_commands_and_info_fail() {
+        let output =
+            converse("OPTION ttyname=/dev/pts/1\nGETINFO flavor\nGETINFO nope\nFOO\n", |_, _| None);
+
+        assert_eq!(output[1..], ["OK", "D rmenu", "OK", UNKNOWN_INFO, UNKNOWN_COMMAND]);
+    }
+}
@@ -0,0 +1,96 @@
+use std::env::var;
+use std::process::Command;
+
+use log::info;
+use sdl2::VideoSubsystem;
+
+// A scale of 1.0 means 96 dots per inch.
+const BASE_DPI: f32 = 96.0;
+
+// Physical size based DPI is rarely exact, and a 1.07
+// scale would only make everything blurry.
+fn round_scale(scale: f32) -> f32 {
+    ((scale * 4.0).round() / 4.0).max(0.25)
+}
+
+fn gdk_scale() -> Option<f32> {
+    var("GDK_SCALE")
+        .ok()?
+        .trim()
+        .parse::<f32>()
+        .ok()
+        .filter(|scale| *scale > 0.0)
+}
+
+// If xrdb is not installed simply do nothing about it.
+fn xft_dpi() -> Option<f32> {
+    let output = Command::new("xrdb")
+        .arg("-query")
+        .output()
+        .ok()?;
+
+    if !output
+        .status
+        .success()
+    {
+        return None;
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| {
+            line.strip_prefix("Xft.dpi:")
+                .and_then(|dpi| {
+                    dpi.trim()
+                        .parse::<f32>()
+                        .ok()
+                })
+        })
+        .filter(|dpi| *dpi > 0.0)
+}
+
+/// Detects the scale factor for a display.
+///
+/// The sources are consulted in order, `GDK_SCALE`, the display DPI
+/// reported by SDL (which is per monitor) and finally `Xft.dpi`,
+/// if none is available the scale is 1.0.
+pub fn detect_display_scale(video: &VideoSubsystem, display_index: i32) -> f32 {
+    if let Some(scale) = gdk_scale() {
+        info!("Using GDK_SCALE as scale factor ({scale})");
+        return scale;
+    }
+
+    if let Ok((diagonal_dpi, ..)) = video.display_dpi(display_index)
+        && diagonal_dpi > 0.0
+    {
+        let scale = round_scale(diagonal_dpi / BASE_DPI);
+        info!("Using display {display_index} DPI ({diagonal_dpi}) as scale factor ({scale})");
+        return scale;
+    }
+
+    if let Some(dpi) = xft_dpi() {
+        let scale = round_scale(dpi / BASE_DPI);
+        info!("Using Xft.dpi ({dpi}) as scale factor ({scale})");
+        return scale;
+    }
+
+    info!("Couldn't detect a scale factor, falling back to 1.0");
+    1.0
+}
+
+/// Scales an unsigned pixel measure, never returning 0
+/// for a non zero measure so thin lines don't disappear.
+#[inline]
+pub fn scale_u32(value: u32, scale: f32) -> u32 {
+    if value == 0 {
+        return 0;
+    }
+
+    ((value as f32 * scale).round() as u32).max(1)
+}
+
+/// Scales a signed pixel measure.
+#[inline]
+pub fn scale_i32(value: i32, scale: f32) -> i32 {
+    (value as f32 * scale).round() as i32
+}
@@ -16,6 +16,7 @@ impl From<String> for GenericComponentError {
     }
 }
 
+#[macro_export]
 macro_rules! handle_app_error {
     ($expr:expr) => {
         match { $expr } {
@@ -28,7 +29,7 @@ macro_rules! handle_app_error {
     };
 }
 
-pub(crate) use handle_app_error;
+pub use handle_app_error;
 use sdl2::render::TextureValueError;
 use sdl2::ttf::FontError;
 use thiserror::Error;
@@ -0,0 +1,174 @@
+use std::ptr::eq as ptr_eq;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::BlendMode;
+use sdl2::rwops::RWops;
+use sdl2::surface::Surface;
+use sdl2::ttf::{Font, FontError};
+
+use super::misc::ttf_context;
+
+pub fn load_default_font(size: u16) -> Result<Font<'static, 'static>, String> {
+    ttf_context()
+        .map_err(|err| err.to_string())?
+        .load_font_from_rwops(
+            RWops::from_bytes(include_bytes!("../../assets/default_font.ttf"))?,
+            size,
+        )
+}
+
+/// An ordered list of fonts, the first one is the primary font
+/// and the rest are consulted per glyph when the primary font
+/// doesn't have it, so emoji, CJK or symbols don't render as tofu.
+pub struct FontSet<'f> {
+    fonts: Vec<Font<'f, 'f>>,
+    id: u64,
+}
+
+// Gives every font set its own id, even one
+// reloaded at the address of a dropped one.
+static NEXT_FONT_SET_ID: AtomicU64 = AtomicU64::new(0);
+
+impl<'f> FontSet<'f> {
+    /// Creates a font set, returns `None` if no fonts were provided.
+    pub fn new(fonts: Vec<Font<'f, 'f>>) -> Option<Self> {
+        if fonts.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            fonts,
+            id: NEXT_FONT_SET_ID.fetch_add(1, Ordering::Relaxed),
+        })
+    }
+
+    /// An id no other font set shares, for caching what was rendered with it.
+    #[inline]
+    pub const fn id(&self) -> u64 {
+        self.id
+    }
+
+    #[inline]
+    pub fn primary(&self) -> &Font<'f, 'f> {
+        &self.fonts[0]
+    }
+
+    fn font_for(&self, letter: char) -> &Font<'f, 'f> {
+        self.fonts
+            .iter()
+            .find(|font| {
+                font.find_glyph(letter)
+                    .is_some()
+            })
+            .unwrap_or(self.primary())
+    }
+
+    // Splits the text in runs of characters
+    // that are rendered with the same font.
+    fn runs<'s, 't>(&'s self, text: &'t str) -> Vec<(&'s Font<'f, 'f>, &'t str)> {
+        let mut runs: Vec<(&'s Font<'f, 'f>, &'t str)> = Vec::new();
+        let mut run_start = 0;
+
+        for (index, letter) in text.char_indices() {
+            let font = self.font_for(letter);
+
+            if let Some((run_font, run_text)) = runs.last_mut()
+                && ptr_eq(*run_font, font)
+            {
+                *run_text = &text[run_start..index + letter.len_utf8()];
+                continue;
+            }
+
+            run_start = index;
+            runs.push((font, &text[index..index + letter.len_utf8()]));
+        }
+
+        runs
+    }
+
+    // The vertical offset of every run so
+    // they all share the same baseline.
+    fn baseline(runs: &[(&Font<'f, 'f>, &str)]) -> i32 {
+        runs.iter()
+            .map(|(font, _)| font.ascent())
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn size_of(&self, text: &str) -> Result<(u32, u32), FontError> {
+        let runs = self.runs(text);
+
+        if runs.len() <= 1 {
+            return self
+                .runs_font(&runs)
+                .size_of(text);
+        }
+
+        let baseline = Self::baseline(&runs);
+        let mut width = 0;
+        let mut height = 0;
+
+        for (font, run) in &runs {
+            let (run_width, run_height) = font.size_of(run)?;
+
+            width += run_width;
+            height = height.max((baseline - font.ascent()).max(0) as u32 + run_height);
+        }
+
+        Ok((width, height))
+    }
+
+    pub fn render_blended(&self, text: &str, color: Color) -> Result<Surface<'static>, FontError> {
+        let runs = self.runs(text);
+
+        if runs.len() <= 1 {
+            return self
+                .runs_font(&runs)
+                .render(text)
+                .blended(color);
+        }
+
+        let baseline = Self::baseline(&runs);
+        let (width, height) = self.size_of(text)?;
+
+        let mut surface =
+            Surface::new(width, height, PixelFormatEnum::ARGB8888).map_err(FontError::SdlError)?;
+
+        let mut x_offset = 0;
+        for (font, run) in &runs {
+            let mut run_surface = font
+                .render(run)
+                .blended(color)?;
+
+            // The runs never overlap, so their alpha is
+            // copied as is instead of blended.
+            run_surface
+                .set_blend_mode(BlendMode::None)
+                .map_err(FontError::SdlError)?;
+
+            let run_rect = Rect::new(
+                x_offset,
+                baseline - font.ascent(),
+                run_surface.width(),
+                run_surface.height(),
+            );
+
+            run_surface
+                .blit(None, &mut surface, run_rect)
+                .map_err(FontError::SdlError)?;
+
+            x_offset += run_surface.width() as i32;
+        }
+
+        Ok(surface)
+    }
+
+    #[inline]
+    fn runs_font<'s>(&'s self, runs: &[(&'s Font<'f, 'f>, &str)]) -> &'s Font<'f, 'f> {
+        runs.first()
+            .map(|(font, _)| *font)
+            .unwrap_or(self.primary())
+    }
+}
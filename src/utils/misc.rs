@@ -1,9 +1,10 @@
 use std::sync::OnceLock;
 
+use sdl2::VideoSubsystem;
+use sdl2::keyboard::Mod;
 use sdl2::rect::Point;
-use sdl2::sys::SDL_GetGlobalMouseState;
+use sdl2::sys::{SDL_GetGlobalMouseState, SDL_GetModState};
 use sdl2::ttf::{InitError, Sdl2TtfContext, init as ttf_init};
-use sdl2::VideoSubsystem;
 
 static TTF_CONTEXT: OnceLock<Sdl2TtfContext> = OnceLock::new();
 
@@ -34,3 +35,10 @@ pub fn find_mouse_monitor(video: &VideoSubsystem) -> Result<Option<i32>, String>
 
     Ok(None)
 }
+
+// Mouse events don't carry the modifiers, so they're queried.
+pub fn is_shift_held() -> bool {
+    let modifiers = unsafe { SDL_GetModState() } as u16;
+
+    Mod::from_bits_truncate(modifiers).intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD)
+}
@@ -1,4 +1,7 @@
+pub mod display_scale;
 pub mod errors;
+pub mod fonts;
 pub mod keycode_to_char;
 pub mod misc;
+pub mod shell_words;
 pub mod vector_matrix;
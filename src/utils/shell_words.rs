@@ -0,0 +1,139 @@
+use std::mem::take;
+
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ShellWordsError {
+    #[error("A quote is never closed")]
+    UnclosedQuote,
+
+    #[error("A backslash escapes nothing at the end")]
+    TrailingEscape,
+}
+
+/// Splits a command line in words like a POSIX shell would, without
+/// expanding anything, whitespace separates words, quotes group them
+/// and backslashes escape the next character.
+pub fn split_words(text: &str) -> Result<Vec<String>, ShellWordsError> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    // Quotes can make an empty word, like `''`.
+    let mut in_word = false;
+
+    let mut chars = text.chars();
+
+    while let Some(character) = chars.next() {
+        match character {
+            '\'' => {
+                in_word = true;
+
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(character) => word.push(character),
+                        None => return Err(ShellWordsError::UnclosedQuote),
+                    }
+                }
+            },
+
+            '"' => {
+                in_word = true;
+
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+
+                        // Only these are escaped inside double quotes.
+                        Some('\\') => match chars.next() {
+                            Some(escaped @ ('"' | '\\' | '$' | '`')) => word.push(escaped),
+                            Some('\n') => {},
+                            Some(character) => {
+                                word.push('\\');
+                                word.push(character);
+                            },
+                            None => return Err(ShellWordsError::UnclosedQuote),
+                        },
+
+                        Some(character) => word.push(character),
+                        None => return Err(ShellWordsError::UnclosedQuote),
+                    }
+                }
+            },
+
+            '\\' => match chars.next() {
+                Some('\n') => {},
+                Some(escaped) => {
+                    in_word = true;
+                    word.push(escaped);
+                },
+                None => return Err(ShellWordsError::TrailingEscape),
+            },
+
+            character if character.is_whitespace() => {
+                if in_word {
+                    words.push(take(&mut word));
+                    in_word = false;
+                }
+            },
+
+            character => {
+                in_word = true;
+                word.push(character);
+            },
+        }
+    }
+
+    if in_word {
+        words.push(word);
+    }
+
+    Ok(words)
+}
+
+/// Quotes a word so a shell reads it back as it is.
+pub fn quote_word(word: &str) -> String {
+    if !word.is_empty()
+        && word
+            .chars()
+            .all(|character| character.is_alphanumeric() || "-_./=:,+@%".contains(character))
+    {
+        return word.to_string();
+    }
+
+    format!("'{}'", word.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words_are_split_by_whitespace_and_grouped_by_quotes() {
+        assert_eq!(
+            split_words(r#"alacritty  --title "r menu" -e 'a b'\ c "" {cmd}"#).unwrap(),
+            ["alacritty", "--title", "r menu", "-e", "a b c", "", "{cmd}"]
+        );
+    }
+
+    #[test]
+    fn double_quotes_only_escape_some_characters() {
+        assert_eq!(split_words(r#""\"\$\n""#).unwrap(), [r#""$\n"#]);
+    }
+
+    #[test]
+    fn unfinished_input_fails() {
+        assert_eq!(split_words("'open").unwrap_err(), ShellWordsError::UnclosedQuote);
+        assert_eq!(split_words("end\\").unwrap_err(), ShellWordsError::TrailingEscape);
+    }
+
+    #[test]
+    fn quoted_words_are_read_back() {
+        let words = ["vim", "it's here", ""];
+        let line = words
+            .map(quote_word)
+            .join(" ");
+
+        assert_eq!(line, r"vim 'it'\''s here' ''");
+        assert_eq!(split_words(&line).unwrap(), words);
+    }
+}
@@ -0,0 +1,80 @@
+#![cfg(unix)]
+
+use std::env::{join_paths, set_var, split_paths, temp_dir, var_os};
+use std::fs::{
+    Permissions,
+    create_dir_all,
+    read_to_string,
+    remove_dir_all,
+    set_permissions,
+    write,
+};
+use std::os::unix::fs::PermissionsExt;
+use std::process::{Command, id as process_id};
+
+use rmenu::config::loader::Config;
+use rmenu::launch::spawn_detached;
+use toml_edit::DocumentMut;
+
+// Writes its arguments where the test reads them and starts the
+// program after `--`, as `systemd-run --scope` does.
+const MOCK_SYSTEMD_RUN: &str = "#!/bin/sh
+printf '%s\\n' \"$@\" > \"$RMENU_TEST_OUTPUT.args\"
+while [ \"$1\" != \"--\" ]; do shift; done
+shift
+exec \"$@\"
+";
+
+#[test]
+fn programs_start_through_systemd_run() {
+    let directory = temp_dir().join(format!("rmenu-systemd-scope-{}", process_id()));
+    let mock = directory.join("systemd-run");
+    let output = directory.join("output");
+
+    create_dir_all(&directory).unwrap();
+    write(&mock, MOCK_SYSTEMD_RUN).unwrap();
+    set_permissions(&mock, Permissions::from_mode(0o755)).unwrap();
+
+    let path = join_paths(
+        [directory.clone()]
+            .into_iter()
+            .chain(split_paths(&var_os("PATH").unwrap_or_default())),
+    )
+    .unwrap();
+
+    // Nothing else in this test binary reads the environment.
+    unsafe {
+        set_var("PATH", path);
+    }
+
+    let config = Config::try_from(
+        "launch_backend = \"systemd\""
+            .parse::<DocumentMut>()
+            .unwrap(),
+    )
+    .unwrap();
+
+    let mut command = Command::new("sh");
+    command
+        .args(["-c", "echo started > \"$RMENU_TEST_OUTPUT\""])
+        .env("RMENU_TEST_OUTPUT", &output);
+
+    let (mut child, _) = spawn_detached(&mut command, config.launch_options()).unwrap();
+    let status = child
+        .wait()
+        .unwrap();
+
+    assert!(status.success());
+    assert_eq!(read_to_string(&output).unwrap(), "started\n");
+
+    let args = read_to_string(output.with_extension("args")).unwrap();
+    let args = args
+        .lines()
+        .collect::<Vec<_>>();
+
+    assert_eq!(args[..4], ["--user", "--scope", "--collect", "--quiet"]);
+    assert!(args[4].starts_with("--unit=app-rmenu-sh-"));
+    assert_eq!(args[5..], ["--", "sh", "-c", "echo started > \"$RMENU_TEST_OUTPUT\""]);
+
+    remove_dir_all(directory).unwrap();
+}
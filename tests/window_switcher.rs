@@ -0,0 +1,204 @@
+#![cfg(unix)]
+
+use std::process::{Child, Command, id as process_id};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use rmenu::modes::window::{ClientWindow, Ewmh};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{
+    AtomEnum,
+    ClientMessageEvent,
+    ConnectionExt as _,
+    CreateWindowAux,
+    EventMask,
+    PropMode,
+    Window,
+    WindowClass,
+};
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as _;
+
+// Killed when the test ends, even if it fails.
+struct Server(Child);
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+// Retries until it's true, the window manager
+// handles the requests on its own time.
+fn wait_for(what: &str, mut done: impl FnMut() -> bool) {
+    let start = Instant::now();
+
+    while !done() {
+        assert!(start.elapsed() < Duration::from_secs(10), "Timed out waiting for {what}.");
+        sleep(Duration::from_millis(50));
+    }
+}
+
+// A window of its own connection, so closing one
+// doesn't kill the connection of the others.
+fn open_window(display: &str, name: &str, class: &str) -> (RustConnection, Window) {
+    let (connection, screen) = x11rb::connect(Some(display)).unwrap();
+    let screen = &connection
+        .setup()
+        .roots[screen];
+    let window = connection
+        .generate_id()
+        .unwrap();
+
+    connection
+        .create_window(
+            0,
+            window,
+            screen.root,
+            0,
+            0,
+            100,
+            100,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            screen.root_visual,
+            &CreateWindowAux::new(),
+        )
+        .unwrap();
+
+    let utf8_string = connection
+        .intern_atom(false, b"UTF8_STRING")
+        .unwrap()
+        .reply()
+        .unwrap()
+        .atom;
+    let net_wm_name = connection
+        .intern_atom(false, b"_NET_WM_NAME")
+        .unwrap()
+        .reply()
+        .unwrap()
+        .atom;
+
+    connection
+        .change_property8(PropMode::REPLACE, window, net_wm_name, utf8_string, name.as_bytes())
+        .unwrap();
+    connection
+        .change_property8(
+            PropMode::REPLACE,
+            window,
+            AtomEnum::WM_CLASS,
+            AtomEnum::STRING,
+            format!("{}\0{class}\0", class.to_lowercase()).as_bytes(),
+        )
+        .unwrap();
+    connection
+        .map_window(window)
+        .unwrap();
+    connection
+        .flush()
+        .unwrap();
+
+    (connection, window)
+}
+
+fn listed(ewmh: &Ewmh, window: Window) -> Option<ClientWindow> {
+    ewmh.windows()
+        .unwrap()
+        .into_iter()
+        .find(|listed| listed.id() == window)
+}
+
+// Needs Xvfb and openbox, run it with `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn windows_are_listed_activated_and_closed() {
+    let display = format!(":{}", 100 + process_id() % 100);
+
+    let _xvfb = Server(
+        Command::new("Xvfb")
+            .args([&display, "-screen", "0", "640x480x24"])
+            .spawn()
+            .expect("Xvfb to be installed."),
+    );
+
+    let mut ewmh = None;
+    wait_for("Xvfb", || {
+        ewmh = Ewmh::connect(Some(&display)).ok();
+        ewmh.is_some()
+    });
+    let ewmh = ewmh.unwrap();
+
+    let _openbox = Server(
+        Command::new("openbox")
+            .arg("--sm-disable")
+            .env("DISPLAY", &display)
+            .spawn()
+            .expect("openbox to be installed."),
+    );
+
+    wait_for("openbox", || {
+        ewmh.windows()
+            .is_ok()
+    });
+
+    let (_terminal_connection, terminal) = open_window(&display, "~/projects", "XTerm");
+    let (editor_connection, editor) = open_window(&display, "notes.md", "Gvim");
+
+    wait_for("the windows to be listed", || {
+        listed(&ewmh, terminal).is_some() && listed(&ewmh, editor).is_some()
+    });
+
+    let listed_terminal = listed(&ewmh, terminal).unwrap();
+    assert_eq!(listed_terminal.name(), "~/projects");
+    assert_eq!(listed_terminal.class(), "XTerm");
+    assert_eq!(listed_terminal.desktop(), Some(0));
+
+    // Moved to the second desktop, activating it switches there.
+    let net_wm_desktop = editor_connection
+        .intern_atom(false, b"_NET_WM_DESKTOP")
+        .unwrap()
+        .reply()
+        .unwrap()
+        .atom;
+    let root = editor_connection
+        .setup()
+        .roots[0]
+        .root;
+
+    editor_connection
+        .send_event(
+            false,
+            root,
+            EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+            ClientMessageEvent::new(32, editor, net_wm_desktop, [1, 2, 0, 0, 0]),
+        )
+        .unwrap();
+    editor_connection
+        .flush()
+        .unwrap();
+
+    wait_for("the editor to be moved", || {
+        listed(&ewmh, editor).and_then(|window| window.desktop()) == Some(1)
+    });
+
+    ewmh.activate(&listed(&ewmh, editor).unwrap())
+        .unwrap();
+
+    wait_for("the editor to be activated", || {
+        ewmh.active_window()
+            .unwrap()
+            == Some(editor)
+    });
+    assert_eq!(
+        ewmh.current_desktop()
+            .unwrap(),
+        Some(1)
+    );
+
+    ewmh.close(&listed(&ewmh, editor).unwrap())
+        .unwrap();
+
+    wait_for("the editor to be closed", || listed(&ewmh, editor).is_none());
+    assert!(listed(&ewmh, terminal).is_some());
+}